@@ -1,10 +1,11 @@
+use std::hash::{BuildHasherDefault, Hasher};
 use std::hint::black_box;
 
 use criterion::{Criterion, criterion_group, criterion_main};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 
-use hashset::HashSet;
+use hashset::{FlatHashSet, HashSet};
 
 fn bench_insert(c: &mut Criterion) {
     let mut rng = StdRng::seed_from_u64(123);
@@ -51,5 +52,133 @@ fn bench_contains(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_insert, bench_contains);
+fn bench_is_subset(c: &mut Criterion) {
+    let words: Vec<String> = (0..10_000).map(|i| format!("element-{i}")).collect();
+
+    let my_all: HashSet<String> = words.iter().cloned().collect();
+    let my_half: HashSet<String> = words.iter().step_by(2).cloned().collect();
+    let std_all: std::collections::HashSet<String> = words.iter().cloned().collect();
+    let std_half: std::collections::HashSet<String> = words.iter().step_by(2).cloned().collect();
+
+    c.bench_function("my HashSet is_subset", |b| {
+        b.iter(|| black_box(my_half.is_subset(black_box(&my_all))))
+    });
+
+    c.bench_function("std HashSet is_subset", |b| {
+        b.iter(|| black_box(std_half.is_subset(black_box(&std_all))))
+    });
+}
+
+fn bench_get_or_insert(c: &mut Criterion) {
+    // Mostly-unique keys, so nearly every call takes the miss path
+    let mut rng = StdRng::seed_from_u64(789);
+    let data: Vec<String> = (0..10_000)
+        .map(|_| rng.gen_range(0..1_000_000u32).to_string())
+        .collect();
+
+    c.bench_function("my HashSet get_or_insert", |b| {
+        b.iter(|| {
+            let mut set = HashSet::new();
+            for s in &data {
+                black_box(set.get_or_insert(s.clone()));
+            }
+        })
+    });
+
+    c.bench_function("my HashSet contains + insert", |b| {
+        b.iter(|| {
+            let mut set = HashSet::new();
+            for s in &data {
+                if !set.contains(s) {
+                    set.insert(s.clone());
+                }
+            }
+        })
+    });
+}
+
+// Every key shares one bucket, so an uncached lookup scans the whole chain
+#[derive(Default)]
+struct CollidingHasher;
+
+impl Hasher for CollidingHasher {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {}
+}
+
+// Compare runs with and without `--features query_cache`
+fn bench_contains_repeated(c: &mut Criterion) {
+    let mut set = HashSet::with_hasher(BuildHasherDefault::<CollidingHasher>::default());
+    set.set_max_probe(usize::MAX);
+    for x in 0..1_000u64 {
+        set.insert(x);
+    }
+
+    c.bench_function("my HashSet contains same key", |b| {
+        b.iter(|| black_box(set.contains(black_box(&999))))
+    });
+}
+
+fn bench_flat_layout(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(789);
+    let data: Vec<u64> = (0..10_000).map(|_| rng.r#gen()).collect();
+
+    let bucketed: HashSet<u64> = data.iter().copied().collect();
+    let flat: FlatHashSet<u64> = data.iter().copied().collect();
+
+    c.bench_function("bucketed HashSet iter sum", |b| {
+        b.iter(|| black_box(bucketed.iter().sum::<u64>()))
+    });
+
+    c.bench_function("FlatHashSet iter sum", |b| {
+        b.iter(|| black_box(flat.iter().sum::<u64>()))
+    });
+
+    c.bench_function("bucketed HashSet contains", |b| {
+        b.iter(|| {
+            for x in &data[..1000] {
+                black_box(bucketed.contains(black_box(x)));
+            }
+        })
+    });
+
+    c.bench_function("FlatHashSet contains", |b| {
+        b.iter(|| {
+            for x in &data[..1000] {
+                black_box(flat.contains(black_box(x)));
+            }
+        })
+    });
+}
+
+fn bench_get_batch(c: &mut Criterion) {
+    use rand::seq::SliceRandom;
+
+    let mut rng = StdRng::seed_from_u64(1011);
+    let set: HashSet<u64> = (0..1_000_000).collect();
+    let mut keys: Vec<u64> = (0..100_000).map(|_| rng.gen_range(0..2_000_000)).collect();
+    keys.shuffle(&mut rng);
+
+    c.bench_function("my HashSet get per key", |b| {
+        b.iter(|| black_box(keys.iter().map(|k| set.get(k)).collect::<Vec<_>>()))
+    });
+
+    c.bench_function("my HashSet get_batch_sorted", |b| {
+        b.iter(|| black_box(set.get_batch_sorted(&keys)))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_insert,
+    bench_contains,
+    bench_is_subset,
+    bench_get_or_insert,
+    bench_contains_repeated,
+    bench_flat_layout,
+    bench_get_batch
+);
 criterion_main!(benches);
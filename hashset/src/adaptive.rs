@@ -0,0 +1,204 @@
+use crate::{HashSet, Iter};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+
+/// A set that defends against clustering input by switching hashers.
+///
+/// After every resize it checks the longest chain; if that exceeds
+/// `max_chain`, it builds a new hasher from a fresh seed through `reseed`
+/// and rehashes in place. Each reseed costs one O(n) pass (on top of the
+/// `bucket_stats` scan every resize already pays), so at most
+/// `max_reseeds` happen over the set's lifetime.
+///
+/// New seeds are derived from the previous seed and the reseed count, so
+/// the same initial seed and inserts always give the same layout.
+pub struct AdaptiveHashSet<T, S> {
+    set: HashSet<T, S>,
+    reseed: fn(u64) -> S,
+    seed: u64,
+    max_chain: usize,
+    reseeds_left: usize,
+    reseeds: usize,
+}
+
+impl<T, S> AdaptiveHashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Starts with `reseed(initial_seed)` as the hasher.
+    pub fn new(
+        reseed: fn(u64) -> S,
+        initial_seed: u64,
+        max_chain: usize,
+        max_reseeds: usize,
+    ) -> Self {
+        Self {
+            set: HashSet::with_hasher(reseed(initial_seed)),
+            reseed,
+            seed: initial_seed,
+            max_chain,
+            reseeds_left: max_reseeds,
+            reseeds: 0,
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let resizes_before = self.set.resize_count();
+        let inserted = self.set.insert(value);
+
+        if self.set.resize_count() != resizes_before {
+            self.check_collisions();
+        }
+
+        inserted
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.contains(value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.remove(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.set.iter()
+    }
+
+    /// The seed the current hasher was built from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// How many times the hasher has been replaced.
+    pub fn reseeds(&self) -> usize {
+        self.reseeds
+    }
+
+    pub fn as_set(&self) -> &HashSet<T, S> {
+        &self.set
+    }
+
+    pub fn into_inner(self) -> HashSet<T, S> {
+        self.set
+    }
+
+    fn check_collisions(&mut self) {
+        if self.reseeds_left == 0 || self.set.bucket_stats().longest_chain() <= self.max_chain {
+            return;
+        }
+
+        self.reseeds += 1;
+        self.seed = next_seed(self.seed, self.reseeds);
+        self.set.set_hasher((self.reseed)(self.seed));
+        self.reseeds_left -= 1;
+    }
+}
+
+// SplitMix64's finalizer over the previous seed and the reseed count, so
+// consecutive seeds are unrelated but reproducible.
+fn next_seed(seed: u64, reseeds: usize) -> u64 {
+    let mut z = seed ^ (reseeds as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[test]
+fn test_adaptive_reseeds_on_clustering() {
+    use crate::SeededState;
+    use std::hash::Hasher;
+
+    // Seed 0 is a deliberately weak hasher that keeps only the input's
+    // high bits; any other seed is a proper keyed hash
+    #[derive(Clone)]
+    enum MaybeWeak {
+        Weak,
+        Strong(SeededState),
+    }
+
+    enum MaybeWeakHasher {
+        Weak(u64),
+        Strong(std::collections::hash_map::DefaultHasher),
+    }
+
+    impl Hasher for MaybeWeakHasher {
+        fn finish(&self) -> u64 {
+            match self {
+                MaybeWeakHasher::Weak(v) => *v,
+                MaybeWeakHasher::Strong(h) => h.finish(),
+            }
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            match self {
+                MaybeWeakHasher::Weak(v) => {
+                    for &b in bytes {
+                        *v = (*v << 8) | u64::from(b);
+                    }
+                    *v &= !0xffff;
+                }
+                MaybeWeakHasher::Strong(h) => h.write(bytes),
+            }
+        }
+    }
+
+    impl BuildHasher for MaybeWeak {
+        type Hasher = MaybeWeakHasher;
+
+        fn build_hasher(&self) -> MaybeWeakHasher {
+            match self {
+                MaybeWeak::Weak => MaybeWeakHasher::Weak(0),
+                MaybeWeak::Strong(state) => MaybeWeakHasher::Strong(state.build_hasher()),
+            }
+        }
+    }
+
+    fn reseed(seed: u64) -> MaybeWeak {
+        if seed == 0 {
+            MaybeWeak::Weak
+        } else {
+            MaybeWeak::Strong(SeededState::new(seed | 1))
+        }
+    }
+
+    let fill = |reseed: fn(u64) -> MaybeWeak| {
+        let mut set = AdaptiveHashSet::new(reseed, 0, 8, 3);
+        for i in 0..2000u64 {
+            set.insert(i.to_be());
+        }
+        set
+    };
+
+    let set = fill(reseed);
+    assert_eq!(set.reseeds(), 1);
+    assert_eq!(set.seed(), next_seed(0, 1));
+    assert_eq!(set.len(), 2000);
+    assert!(set.as_set().bucket_stats().longest_chain() <= 8);
+    assert!((0..2000u64).all(|i| set.contains(&i.to_be())));
+
+    // Reseeding is deterministic, so the layout is too
+    assert!(set.iter().eq(fill(reseed).iter()));
+
+    // A hasher that stays weak after every reseed uses up the budget
+    let set = fill(|_| MaybeWeak::Weak);
+    assert_eq!(set.reseeds(), 3);
+    assert_eq!(set.len(), 2000);
+}
@@ -0,0 +1,164 @@
+use crate::{DefaultHashBuilder, HashSet};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const DEFAULT_BUCKETS: usize = 1024;
+
+/// A grow-only set that can be shared between threads. Each bucket has its
+/// own lock, so `insert` and `contains` on different buckets never contend.
+/// There is no removal, and the bucket count only changes through
+/// `resize`, which takes `&mut self` and so cannot race with other callers.
+pub struct AppendOnlyHashSet<T, S = DefaultHashBuilder> {
+    buckets: Box<[RwLock<Vec<T>>]>,
+    size: AtomicUsize,
+    hash_builder: S,
+}
+
+impl<T: Hash + Eq> Default for AppendOnlyHashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq> AppendOnlyHashSet<T> {
+    pub fn new() -> Self {
+        Self::with_buckets(DEFAULT_BUCKETS)
+    }
+
+    /// Pick the bucket count for the expected final size: it stays fixed
+    /// while the set is shared.
+    pub fn with_buckets(bucket_count: usize) -> Self {
+        Self::with_buckets_and_hasher(bucket_count, DefaultHashBuilder::default())
+    }
+}
+
+impl<T, S> AppendOnlyHashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn with_buckets_and_hasher(bucket_count: usize, hash_builder: S) -> Self {
+        Self {
+            buckets: create_buckets(bucket_count.max(1)),
+            size: AtomicUsize::new(0),
+            hash_builder,
+        }
+    }
+
+    fn bucket_of<Q: Hash + ?Sized>(&self, value: &Q) -> &RwLock<Vec<T>> {
+        let hash = self.hash_builder.hash_one(value);
+        &self.buckets[(hash as usize) % self.buckets.len()]
+    }
+
+    pub fn insert(&self, value: T) -> bool {
+        let mut bucket = self.bucket_of(&value).write().unwrap();
+
+        if bucket.contains(&value) {
+            return false;
+        }
+
+        bucket.push(value);
+        self.size.fetch_add(1, Ordering::Relaxed);
+
+        true
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.bucket_of(value)
+            .read()
+            .unwrap()
+            .iter()
+            .any(|v| v.borrow() == value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Redistributes every element over `bucket_count` buckets.
+    pub fn resize(&mut self, bucket_count: usize) {
+        let mut buckets = create_buckets(bucket_count.max(1));
+
+        for bucket in std::mem::take(&mut self.buckets) {
+            for value in bucket.into_inner().unwrap() {
+                let hash = self.hash_builder.hash_one(&value);
+                let index = (hash as usize) % buckets.len();
+                buckets[index].get_mut().unwrap().push(value);
+            }
+        }
+
+        self.buckets = buckets;
+    }
+
+    /// Converts into a regular `HashSet` once the concurrent phase is over.
+    pub fn into_hash_set(self) -> HashSet<T, S> {
+        let mut set = HashSet::with_capacity_and_hasher(self.len(), self.hash_builder);
+
+        for bucket in self.buckets {
+            for value in bucket.into_inner().unwrap() {
+                set.insert(value);
+            }
+        }
+
+        set
+    }
+}
+
+fn create_buckets<T>(size: usize) -> Box<[RwLock<Vec<T>>]> {
+    std::iter::repeat_with(|| RwLock::new(Vec::new()))
+        .take(size)
+        .collect()
+}
+
+#[test]
+fn test_append_only_concurrent_inserts() {
+    let set = AppendOnlyHashSet::with_buckets(64);
+
+    // Each thread covers an overlapping window of 0..1700
+    std::thread::scope(|scope| {
+        for t in 0..8 {
+            let set = &set;
+            scope.spawn(move || {
+                for i in t * 200..t * 200 + 300 {
+                    set.insert(i);
+                    assert!(set.contains(&i));
+                }
+            });
+        }
+    });
+
+    assert_eq!(set.len(), 1700);
+    assert!((0..1700).all(|i| set.contains(&i)));
+    assert!(!set.contains(&1700));
+
+    let set = set.into_hash_set();
+    assert_eq!(set.len(), 1700);
+}
+
+#[test]
+fn test_append_only_resize() {
+    let mut set = AppendOnlyHashSet::with_buckets(2);
+    for i in 0..100 {
+        assert!(set.insert(i));
+    }
+    assert!(!set.insert(5));
+
+    set.resize(128);
+    assert_eq!(set.bucket_count(), 128);
+    assert_eq!(set.len(), 100);
+    assert!((0..100).all(|i| set.contains(&i)));
+}
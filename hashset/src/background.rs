@@ -0,0 +1,174 @@
+use crate::HashSet;
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// A set that grows its table on a background thread instead of stalling
+/// the inserting thread for one large rehash.
+///
+/// When an insert would push the table past its load factor, a worker
+/// thread starts building a table with twice the buckets from a shared
+/// snapshot of the current one. Until it finishes, the old table keeps
+/// serving reads, and new elements go to a small staging set that is
+/// merged in when the tables are swapped.
+///
+/// Consistency model:
+/// - `contains` and `len` always see every element inserted so far (the
+///   old table plus staging).
+/// - The swap happens on the thread calling `insert` or `finish_resize`,
+///   never concurrently with a reader.
+/// - `remove` has to see a single table, so it waits for a pending rebuild
+///   to finish first.
+pub struct BackgroundHashSet<T> {
+    current: Arc<HashSet<T>>,
+    staging: HashSet<T>,
+    pending: Option<JoinHandle<HashSet<T>>>,
+    background_resizes: usize,
+}
+
+impl<T> Default for BackgroundHashSet<T>
+where
+    T: Hash + Eq + Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> BackgroundHashSet<T>
+where
+    T: Hash + Eq + Clone + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            current: Arc::new(HashSet::with_capacity(capacity)),
+            staging: HashSet::new(),
+            pending: None,
+            background_resizes: 0,
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        self.poll_resize();
+
+        if self.contains(&value) {
+            return false;
+        }
+
+        let full = (self.current.len() + 1) * 4 > self.current.capacity() * 3;
+
+        if self.pending.is_none() && full {
+            self.start_resize();
+        }
+
+        match Arc::get_mut(&mut self.current) {
+            Some(current) if self.pending.is_none() => current.insert(value),
+            _ => self.staging.insert(value),
+        }
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.current.contains(value) || self.staging.contains(value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.finish_resize();
+        self.table_mut().remove(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.current.len() + self.staging.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_resizing(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// How many table swaps have completed.
+    pub fn background_resizes(&self) -> usize {
+        self.background_resizes
+    }
+
+    /// Blocks until a pending rebuild finishes and swaps it in.
+    pub fn finish_resize(&mut self) {
+        if let Some(handle) = self.pending.take() {
+            let rebuilt = handle.join().expect("background resize panicked");
+            self.swap_in(rebuilt);
+        }
+    }
+
+    pub fn into_inner(mut self) -> HashSet<T> {
+        self.finish_resize();
+        std::mem::take(self.table_mut())
+    }
+
+    fn start_resize(&mut self) {
+        let snapshot = Arc::clone(&self.current);
+        // Room for twice the current buckets' worth of elements
+        let target = snapshot.capacity() * 2 * 3 / 4;
+
+        self.pending = Some(std::thread::spawn(move || {
+            let mut rebuilt = HashSet::with_capacity(target);
+            for value in snapshot.iter() {
+                rebuilt.insert(value.clone());
+            }
+            rebuilt
+        }));
+    }
+
+    fn poll_resize(&mut self) {
+        if self.pending.as_ref().is_some_and(JoinHandle::is_finished) {
+            self.finish_resize();
+        }
+    }
+
+    fn swap_in(&mut self, mut rebuilt: HashSet<T>) {
+        rebuilt.extend(self.staging.drain());
+        self.current = Arc::new(rebuilt);
+        self.background_resizes += 1;
+    }
+
+    // Only reached with no rebuild pending, when nothing else holds the Arc
+    fn table_mut(&mut self) -> &mut HashSet<T> {
+        Arc::get_mut(&mut self.current).expect("table shared outside a rebuild")
+    }
+}
+
+#[test]
+fn test_background_resize_keeps_elements() {
+    let mut set = BackgroundHashSet::new();
+    for i in 0..50_000u64 {
+        assert!(set.insert(i));
+        assert!(!set.insert(i / 2)); // Duplicates while a rebuild may be running
+    }
+    // Join any rebuild still running so the counts below don't race it
+    set.finish_resize();
+    assert!(!set.is_resizing());
+    assert!(set.background_resizes() > 0);
+    assert_eq!(set.len(), 50_000);
+    assert!((0..50_000).all(|i| set.contains(&i)));
+
+    assert!(set.remove(&7));
+    assert!(!set.is_resizing());
+
+    let set = set.into_inner();
+    assert_eq!(set.len(), 49_999);
+    assert!(!set.contains(&7));
+}
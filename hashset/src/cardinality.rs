@@ -0,0 +1,93 @@
+use crate::DefaultHashBuilder;
+use std::hash::{BuildHasher, Hash};
+
+// 2^14 registers: about 0.8% standard error in 16 KiB
+const PRECISION: u32 = 14;
+const REGISTERS: usize = 1 << PRECISION;
+
+/// A HyperLogLog counter for the approximate number of distinct values in
+/// a stream, in constant memory. The top `PRECISION` bits of each hash pick
+/// a register, which keeps the longest run of leading zeros seen in the
+/// remaining bits.
+#[derive(Debug, Clone)]
+pub struct CardinalityEstimator {
+    registers: Box<[u8]>,
+    hash_builder: DefaultHashBuilder,
+}
+
+impl Default for CardinalityEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CardinalityEstimator {
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; REGISTERS].into_boxed_slice(),
+            hash_builder: DefaultHashBuilder::default(),
+        }
+    }
+
+    pub fn add<T: Hash + ?Sized>(&mut self, value: &T) {
+        let hash = self.hash_builder.hash_one(value);
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // The marker bit caps the run for an all-zero remainder
+        let rest = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    pub fn estimate(&self) -> usize {
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&r| 2f64.powi(-i32::from(r)))
+            .sum();
+        let raw = alpha * m * m / sum;
+
+        // Small cardinalities leave registers empty; linear counting is far
+        // more accurate there
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && zeros > 0 {
+            return (m * (m / zeros as f64).ln()).round() as usize;
+        }
+
+        raw.round() as usize
+    }
+
+    /// Folds `other` in, as if every value it saw had been added here.
+    pub fn merge(&mut self, other: &CardinalityEstimator) {
+        for (mine, theirs) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *mine = (*mine).max(*theirs);
+        }
+    }
+}
+
+#[test]
+fn test_cardinality_estimate() {
+    let mut estimator = CardinalityEstimator::new();
+    assert_eq!(estimator.estimate(), 0);
+
+    for i in 0..100_000u64 {
+        estimator.add(&i);
+        estimator.add(&(i / 2)); // Repeats don't count
+    }
+    let estimate = estimator.estimate() as f64;
+    assert!(
+        (estimate - 100_000.0).abs() < 3_000.0,
+        "estimate {estimate}"
+    );
+
+    let mut small = CardinalityEstimator::new();
+    for i in 0..100u64 {
+        small.add(&i);
+    }
+    assert!(small.estimate().abs_diff(100) <= 3);
+
+    estimator.merge(&small);
+    assert!((estimator.estimate() as f64 - estimate).abs() < 1.0);
+}
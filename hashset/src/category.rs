@@ -0,0 +1,103 @@
+use crate::{HashSet, Iter};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A set that keeps a running count of members per category, where the
+/// category of each element is derived by `key_fn`.
+pub struct CategoryHashSet<T, K> {
+    set: HashSet<T>,
+    key_fn: Box<dyn Fn(&T) -> K>,
+    counts: HashMap<K, usize>,
+}
+
+impl<T, K> CategoryHashSet<T, K>
+where
+    T: Hash + Eq,
+    K: Hash + Eq,
+{
+    pub fn new(key_fn: impl Fn(&T) -> K + 'static) -> Self {
+        Self {
+            set: HashSet::new(),
+            key_fn: Box::new(key_fn),
+            counts: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let category = (self.key_fn)(&value);
+
+        if !self.set.insert(value) {
+            return false;
+        }
+
+        *self.counts.entry(category).or_insert(0) += 1;
+
+        true
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.set.hash_of(value);
+
+        let Some(removed) = self.set.take_hashed(hash, |v| v.borrow() == value) else {
+            return false;
+        };
+
+        let category = (self.key_fn)(&removed);
+        if let Some(count) = self.counts.get_mut(&category) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&category);
+            }
+        }
+
+        true
+    }
+
+    pub fn count_in_category(&self, category: &K) -> usize {
+        self.counts.get(category).copied().unwrap_or(0)
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.contains(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.set.iter()
+    }
+}
+
+#[test]
+fn test_category_counts() {
+    let mut set = CategoryHashSet::new(|v: &u32| v.is_multiple_of(2));
+    for i in 0..10 {
+        set.insert(i);
+    }
+    assert!(!set.insert(4)); // Duplicates don't count twice
+    assert_eq!(set.count_in_category(&true), 5);
+    assert_eq!(set.count_in_category(&false), 5);
+
+    set.remove(&0);
+    set.remove(&2);
+    set.remove(&3);
+    assert!(!set.remove(&3));
+    assert_eq!(set.count_in_category(&true), 3);
+    assert_eq!(set.count_in_category(&false), 4);
+    assert_eq!(set.len(), 7);
+}
@@ -0,0 +1,108 @@
+//! A minimal little-endian binary format for `HashSet::to_bytes` and
+//! `HashSet::from_bytes`: a `u64` element count followed by each element.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    UnexpectedEof,
+    InvalidUtf8,
+    InvalidBool(u8),
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof => f.write_str("unexpected end of input"),
+            DecodeError::InvalidUtf8 => f.write_str("string is not valid UTF-8"),
+            DecodeError::InvalidBool(byte) => write!(f, "invalid bool byte {byte}"),
+            DecodeError::TrailingBytes(n) => write!(f, "{n} trailing bytes after the last element"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+pub trait Encode {
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+pub trait Decode: Sized {
+    /// The fewest bytes any encoded value takes, used to reject element
+    /// counts the input could not hold. The default of 0 makes no promise,
+    /// and then nothing is reserved up front.
+    const MIN_ENCODED_LEN: usize = 0;
+
+    /// Decodes one value from the front of `input`, advancing it.
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError>;
+}
+
+pub(crate) fn take<'a>(input: &mut &'a [u8], n: usize) -> Result<&'a [u8], DecodeError> {
+    if input.len() < n {
+        return Err(DecodeError::UnexpectedEof);
+    }
+
+    let (head, rest) = input.split_at(n);
+    *input = rest;
+
+    Ok(head)
+}
+
+macro_rules! impl_codec_int {
+    ($($t:ty),*) => {$(
+        impl Encode for $t {
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+
+        impl Decode for $t {
+            const MIN_ENCODED_LEN: usize = std::mem::size_of::<$t>();
+
+            fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+                let bytes = take(input, std::mem::size_of::<$t>())?;
+                Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    )*};
+}
+
+impl_codec_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Encode for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+}
+
+impl Decode for bool {
+    const MIN_ENCODED_LEN: usize = 1;
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        match u8::decode(input)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(DecodeError::InvalidBool(byte)),
+        }
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Decode for String {
+    // The length prefix alone
+    const MIN_ENCODED_LEN: usize = 8;
+
+    fn decode(input: &mut &[u8]) -> Result<Self, DecodeError> {
+        let len = usize::try_from(u64::decode(input)?).map_err(|_| DecodeError::UnexpectedEof)?;
+        let bytes = take(input, len)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidUtf8)
+    }
+}
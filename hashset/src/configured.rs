@@ -0,0 +1,170 @@
+use crate::{HashSet, Iter};
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+type ResizeHook = Box<dyn FnMut(usize, usize)>;
+
+/// A set that reports bucket-count changes to an optional hook, for logging
+/// or metrics on growth without polling `resize_count`. The hook gets
+/// `(old_bucket_count, new_bucket_count)` once per resize, so an insert
+/// that grows the table twice (for load and then for chain length) reports
+/// both steps. It runs after the set is consistent again and cannot reach
+/// the set itself, so it is never invoked re-entrantly.
+pub struct ConfiguredHashSet<T> {
+    set: HashSet<T>,
+    on_resize: Option<ResizeHook>,
+}
+
+impl<T: Hash + Eq> Default for ConfiguredHashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ConfiguredHashSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new() -> Self {
+        let mut set = HashSet::new();
+        set.record_resizes();
+
+        Self {
+            set,
+            on_resize: None,
+        }
+    }
+
+    pub fn set_on_resize(&mut self, hook: impl FnMut(usize, usize) + 'static) {
+        self.on_resize = Some(Box::new(hook));
+    }
+
+    pub fn clear_on_resize(&mut self) {
+        self.on_resize = None;
+    }
+
+    /// See `HashSet::set_max_probe`.
+    pub fn set_max_probe(&mut self, max_probe: usize) {
+        self.set.set_max_probe(max_probe);
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        self.observe(|set| set.insert(value))
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.observe(|set| set.reserve(additional));
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.observe(HashSet::shrink_to_fit);
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.contains(value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.remove(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.set.capacity()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.set.iter()
+    }
+
+    pub fn into_inner(self) -> HashSet<T> {
+        self.set
+    }
+
+    // The set logs each resize as it happens; a rehash to the same bucket
+    // count is left out, since nothing observable about the capacity
+    // changed.
+    fn observe<R>(&mut self, op: impl FnOnce(&mut HashSet<T>) -> R) -> R {
+        let result = op(&mut self.set);
+        let resizes = self.set.take_resizes();
+
+        if let Some(hook) = &mut self.on_resize {
+            for (before, after) in resizes {
+                hook(before, after);
+            }
+        }
+
+        result
+    }
+}
+
+#[test]
+fn test_configured_on_resize() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let events: Rc<RefCell<Vec<(usize, usize)>>> = Rc::default();
+    let mut set = ConfiguredHashSet::new();
+    let recorded = Rc::clone(&events);
+    set.set_on_resize(move |old, new| recorded.borrow_mut().push((old, new)));
+
+    for i in 0..100 {
+        set.insert(i);
+    }
+    assert_eq!(events.take(), [(16, 32), (32, 64), (64, 128), (128, 256)]);
+
+    set.reserve(1000);
+    assert_eq!(
+        events.take(),
+        [(256, HashSet::<i32>::required_buckets(1100))]
+    );
+
+    set.clear_on_resize();
+    set.shrink_to_fit();
+    assert!(events.take().is_empty());
+}
+
+#[test]
+fn test_configured_reports_each_resize_of_one_insert() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // Fill to just below the load threshold, then find a value whose insert
+    // grows the table for load and then again for its chain length
+    let mut mirror: HashSet<u32> = (0..12).collect();
+    mirror.set_max_probe(1);
+    let value = (12..10_000)
+        .find(|&v| {
+            let mut probe = mirror.clone();
+            probe.insert(v);
+            probe.resize_count() == mirror.resize_count() + 2
+        })
+        .unwrap();
+
+    let events: Rc<RefCell<Vec<(usize, usize)>>> = Rc::default();
+    let mut set = ConfiguredHashSet::new();
+    for i in 0..12 {
+        set.insert(i);
+    }
+    let recorded = Rc::clone(&events);
+    set.set_on_resize(move |old, new| recorded.borrow_mut().push((old, new)));
+    set.set_max_probe(1);
+
+    set.insert(value);
+    assert_eq!(events.take(), [(16, 32), (32, 64)]);
+}
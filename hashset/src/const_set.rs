@@ -0,0 +1,137 @@
+use crate::Iter;
+use std::borrow::Borrow;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// A set with a fixed, compile-time bucket count. The bucket array is
+/// allocated once and never resized; inserting past a healthy load just
+/// makes the chains longer.
+pub struct ConstHashSet<T, const BUCKETS: usize> {
+    buckets: [Vec<T>; BUCKETS],
+    size: usize,
+}
+
+impl<T: Hash + Eq + fmt::Debug, const BUCKETS: usize> fmt::Debug for ConstHashSet<T, BUCKETS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Hash + Eq, const BUCKETS: usize> Default for ConstHashSet<T, BUCKETS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const BUCKETS: usize> ConstHashSet<T, BUCKETS>
+where
+    T: Hash + Eq,
+{
+    pub fn new() -> Self {
+        const { assert!(BUCKETS > 0, "ConstHashSet needs at least one bucket") };
+
+        Self {
+            buckets: std::array::from_fn(|_| Vec::new()),
+            size: 0,
+        }
+    }
+
+    fn bucket_index<Q: Hash + ?Sized>(value: &Q) -> usize {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        (hasher.finish() as usize) % BUCKETS
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let bucket = &mut self.buckets[Self::bucket_index(&value)];
+
+        if bucket.iter().any(|v| v == &value) {
+            return false;
+        }
+
+        bucket.push(value);
+        self.size += 1;
+
+        true
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.buckets[Self::bucket_index(value)]
+            .iter()
+            .any(|v| v.borrow() == value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let bucket = &mut self.buckets[Self::bucket_index(value)];
+
+        if let Some(pos) = bucket.iter().position(|v| v.borrow() == value) {
+            bucket.swap_remove(pos);
+            self.size -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        BUCKETS
+    }
+
+    pub fn clear(&mut self) {
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+        self.size = 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.buckets)
+    }
+}
+
+#[test]
+fn test_const_insert_beyond_buckets() {
+    let mut set = ConstHashSet::<u32, 8>::new();
+    for i in 0..50 {
+        assert!(set.insert(i));
+    }
+    assert!(!set.insert(7)); // Duplicate
+    assert_eq!(set.len(), 50);
+    assert_eq!(set.capacity(), 8); // Never resized
+    for i in 0..50 {
+        assert!(set.contains(&i));
+    }
+    assert!(!set.contains(&50));
+    assert_eq!(set.iter().count(), 50);
+}
+
+#[test]
+fn test_const_remove() {
+    let mut set = ConstHashSet::<u32, 8>::new();
+    for i in 0..20 {
+        set.insert(i);
+    }
+    assert!(set.remove(&5));
+    assert!(!set.remove(&5));
+    assert_eq!(set.len(), 19);
+    assert!(!set.contains(&5));
+    set.clear();
+    assert!(set.is_empty());
+}
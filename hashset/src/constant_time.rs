@@ -0,0 +1,68 @@
+/// Equality without data-dependent branches, in the spirit of `subtle`'s
+/// trait of the same name. `ct_eq` returns `1` for equal and `0` otherwise,
+/// so results can be combined with `|`/`&` without short-circuiting.
+///
+/// Byte-sequence impls still reveal whether the lengths differ.
+pub trait ConstantTimeEq {
+    fn ct_eq(&self, other: &Self) -> u8;
+}
+
+macro_rules! impl_ct_eq_int {
+    ($($t:ty => $u:ty),* $(,)?) => {$(
+        impl ConstantTimeEq for $t {
+            fn ct_eq(&self, other: &Self) -> u8 {
+                let diff = (*self ^ *other) as $u;
+                // The top bit of `diff | -diff` is set iff `diff != 0`
+                ((diff | diff.wrapping_neg()) >> (<$u>::BITS - 1)) as u8 ^ 1
+            }
+        }
+    )*};
+}
+
+impl_ct_eq_int!(
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize,
+);
+
+impl ConstantTimeEq for [u8] {
+    fn ct_eq(&self, other: &Self) -> u8 {
+        if self.len() != other.len() {
+            return 0;
+        }
+
+        let diff = self
+            .iter()
+            .zip(other)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        diff.ct_eq(&0)
+    }
+}
+
+impl<const N: usize> ConstantTimeEq for [u8; N] {
+    fn ct_eq(&self, other: &Self) -> u8 {
+        self[..].ct_eq(&other[..])
+    }
+}
+
+impl ConstantTimeEq for Vec<u8> {
+    fn ct_eq(&self, other: &Self) -> u8 {
+        self[..].ct_eq(&other[..])
+    }
+}
+
+impl ConstantTimeEq for String {
+    fn ct_eq(&self, other: &Self) -> u8 {
+        self.as_bytes().ct_eq(other.as_bytes())
+    }
+}
+
+#[test]
+fn test_ct_eq() {
+    assert_eq!(5u32.ct_eq(&5), 1);
+    assert_eq!(5u32.ct_eq(&6), 0);
+    assert_eq!((-1i64).ct_eq(&-1), 1);
+    assert_eq!(i64::MIN.ct_eq(&0), 0);
+    assert_eq!(b"key"[..].ct_eq(&b"key"[..]), 1);
+    assert_eq!(b"key"[..].ct_eq(&b"kez"[..]), 0);
+    assert_eq!(String::from("a").ct_eq(&String::from("ab")), 0);
+}
@@ -0,0 +1,176 @@
+use crate::{HashSet, Iter};
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+const HASHES: u64 = 4;
+// Counters per expected element, for roughly a 2% false-positive rate
+const COUNTERS_PER_ELEMENT: usize = 8;
+const MIN_COUNTERS: usize = 1024;
+
+/// An exact set fronted by a counting Bloom filter, so most misses are
+/// rejected without probing the table. Each element bumps `HASHES` small
+/// counters, and removal decrements them again, which keeps the filter
+/// accurate under deletions where a plain Bloom filter would only fill up.
+///
+/// Counters are `u8`. One that reaches 255 saturates and stays there for
+/// good, since after overflow it is unknown how many elements still rely on
+/// it; a stuck counter can only cause extra false positives, never a false
+/// rejection. The filter is sized once from the expected capacity, so
+/// growing well past it raises the false-positive rate but stays correct.
+pub struct CountingBloomHashSet<T> {
+    set: HashSet<T>,
+    counters: Box<[u8]>,
+}
+
+impl<T: Hash + Eq> Default for CountingBloomHashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> CountingBloomHashSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let counters = capacity
+            .saturating_mul(COUNTERS_PER_ELEMENT)
+            .max(MIN_COUNTERS);
+
+        Self {
+            set: HashSet::with_capacity(capacity),
+            counters: vec![0; counters].into_boxed_slice(),
+        }
+    }
+
+    // Double hashing: the two halves of one hash give all `HASHES` probes
+    fn counter_indices(&self, hash: u64) -> impl Iterator<Item = usize> + use<T> {
+        let len = self.counters.len() as u64;
+        let (h1, h2) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+
+        (0..HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let hash = self.set.hash_of(&value);
+
+        if !self.set.insert(value) {
+            return false;
+        }
+
+        for index in self.counter_indices(hash) {
+            self.counters[index] = self.counters[index].saturating_add(1);
+        }
+
+        true
+    }
+
+    /// The filter's answer alone: `false` means definitely absent, `true`
+    /// means possibly present.
+    pub fn may_contain<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.set.hash_of(value);
+        self.counter_indices(hash)
+            .all(|index| self.counters[index] > 0)
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.may_contain(value) && self.set.contains(value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.set.hash_of(value);
+
+        if self
+            .set
+            .take_hashed(hash, |v| v.borrow() == value)
+            .is_none()
+        {
+            return false;
+        }
+
+        for index in self.counter_indices(hash) {
+            let counter = &mut self.counters[index];
+            if *counter != u8::MAX {
+                *counter -= 1;
+            }
+        }
+
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.set.iter()
+    }
+}
+
+#[test]
+fn test_counting_bloom_tracks_removals() {
+    let mut set = CountingBloomHashSet::with_capacity(1000);
+    for i in 0..1000 {
+        assert!(set.insert(i));
+    }
+    assert!(!set.insert(0));
+    assert!((0..1000).all(|i| set.may_contain(&i))); // No false negatives
+
+    let false_positives = (1000..11_000).filter(|i| set.may_contain(i)).count();
+    assert!(false_positives < 500, "{false_positives} false positives");
+
+    for i in (0..1000).step_by(2) {
+        assert!(set.remove(&i));
+    }
+    assert!(!set.remove(&0));
+    for i in 0..1000 {
+        assert_eq!(set.contains(&i), i % 2 == 1);
+        if i % 2 == 1 {
+            assert!(set.may_contain(&i)); // Still no false negatives
+        }
+    }
+
+    // Once everything is gone every counter is back to zero
+    for i in (1..1000).step_by(2) {
+        set.remove(&i);
+    }
+    assert!(set.is_empty());
+    assert!((0..1000).all(|i| !set.may_contain(&i)));
+}
+
+#[test]
+fn test_counting_bloom_saturation() {
+    // A tiny filter where every element shares counters
+    let mut set = CountingBloomHashSet {
+        set: HashSet::new(),
+        counters: vec![0; 1].into_boxed_slice(),
+    };
+    for i in 0..300 {
+        set.insert(i);
+    }
+    for i in 0..300 {
+        set.remove(&i);
+    }
+    assert_eq!(set.counters[0], u8::MAX); // Saturated counters stick
+    assert!(!set.contains(&0));
+}
@@ -0,0 +1,212 @@
+use crate::HashSet;
+use std::fmt;
+use std::hash::Hash;
+
+const DEFAULT_DENSE_LIMIT: usize = 4096;
+// One byte per key below the limit, so this caps the bitmap at 16 MiB
+const MAX_DENSE_LIMIT: usize = 1 << 24;
+
+/// An unsigned integer key that `DenseHashSet` can use directly as an index.
+pub trait DenseKey: Copy + Hash + Eq {
+    fn to_index(self) -> usize;
+
+    /// Only called with indices that came from `to_index`.
+    fn from_index(index: usize) -> Self;
+}
+
+macro_rules! impl_dense_key {
+    ($($t:ty),*) => {$(
+        impl DenseKey for $t {
+            fn to_index(self) -> usize {
+                self as usize
+            }
+
+            fn from_index(index: usize) -> Self {
+                index as $t
+            }
+        }
+    )*};
+}
+
+impl_dense_key!(u8, u16, u32, usize);
+
+/// A set of small integer keys backed by a `Vec<bool>` while every key stays
+/// below `dense_limit`, switching to a regular `HashSet` the first time a
+/// larger key is inserted. The switch is one-way.
+///
+/// The limit is capped at 2^24 keys, so a careless limit can't let the
+/// bitmap grow past 16 MiB; keys beyond the cap go to the hash table.
+#[derive(Clone)]
+pub struct DenseHashSet<K = usize> {
+    storage: Storage<K>,
+    dense_limit: usize,
+}
+
+#[derive(Clone)]
+enum Storage<K> {
+    Dense { present: Vec<bool>, size: usize },
+    Hashed(HashSet<K>),
+}
+
+impl<K: DenseKey + fmt::Debug> fmt::Debug for DenseHashSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.storage {
+            Storage::Dense { present, .. } => f
+                .debug_set()
+                .entries(
+                    present
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, present)| **present)
+                        .map(|(index, _)| K::from_index(index)),
+                )
+                .finish(),
+            Storage::Hashed(set) => set.fmt(f),
+        }
+    }
+}
+
+impl<K: DenseKey> Default for DenseHashSet<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: DenseKey> DenseHashSet<K> {
+    pub fn new() -> Self {
+        Self::with_dense_limit(DEFAULT_DENSE_LIMIT)
+    }
+
+    /// `dense_limit` is capped at 2^24.
+    pub fn with_dense_limit(dense_limit: usize) -> Self {
+        Self {
+            storage: Storage::Dense {
+                present: Vec::new(),
+                size: 0,
+            },
+            dense_limit: dense_limit.min(MAX_DENSE_LIMIT),
+        }
+    }
+
+    pub fn dense_limit(&self) -> usize {
+        self.dense_limit
+    }
+
+    pub fn insert(&mut self, value: K) -> bool {
+        let index = value.to_index();
+        if index >= self.dense_limit {
+            self.fall_back();
+        }
+
+        match &mut self.storage {
+            Storage::Dense { present, size } => {
+                if index >= present.len() {
+                    present.resize(index + 1, false);
+                }
+
+                if present[index] {
+                    return false;
+                }
+
+                present[index] = true;
+                *size += 1;
+
+                true
+            }
+            Storage::Hashed(set) => set.insert(value),
+        }
+    }
+
+    pub fn contains(&self, value: &K) -> bool {
+        match &self.storage {
+            Storage::Dense { present, .. } => {
+                present.get(value.to_index()).copied().unwrap_or(false)
+            }
+            Storage::Hashed(set) => set.contains(value),
+        }
+    }
+
+    pub fn remove(&mut self, value: &K) -> bool {
+        match &mut self.storage {
+            Storage::Dense { present, size } => match present.get_mut(value.to_index()) {
+                Some(slot) if *slot => {
+                    *slot = false;
+                    *size -= 1;
+                    true
+                }
+                _ => false,
+            },
+            Storage::Hashed(set) => set.remove(value),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Dense { size, .. } => *size,
+            Storage::Hashed(set) => set.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_dense(&self) -> bool {
+        matches!(self.storage, Storage::Dense { .. })
+    }
+
+    fn fall_back(&mut self) {
+        if let Storage::Dense { present, .. } = &self.storage {
+            let set = present
+                .iter()
+                .enumerate()
+                .filter(|(_, present)| **present)
+                .map(|(index, _)| K::from_index(index))
+                .collect();
+
+            self.storage = Storage::Hashed(set);
+        }
+    }
+}
+
+#[test]
+fn test_dense_keys() {
+    let mut set = DenseHashSet::<usize>::new();
+    for i in (0..100).step_by(3) {
+        assert!(set.insert(i));
+    }
+    assert!(!set.insert(3)); // Duplicate
+    assert!(set.is_dense());
+    assert_eq!(set.len(), 34);
+    for i in 0..100 {
+        assert_eq!(set.contains(&i), i % 3 == 0);
+    }
+    assert!(set.remove(&3));
+    assert!(!set.contains(&3));
+    assert!(!set.contains(&1_000_000)); // Out of range lookups are fine
+}
+
+#[test]
+fn test_dense_fallback() {
+    let mut set = DenseHashSet::<u32>::with_dense_limit(64);
+    set.insert(1);
+    set.insert(2);
+    assert!(set.is_dense());
+    assert!(set.insert(1_000_000));
+    assert!(!set.is_dense());
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(&1));
+    assert!(set.contains(&2));
+    assert!(set.contains(&1_000_000));
+}
+
+#[test]
+fn test_dense_limit_capped() {
+    let mut set = DenseHashSet::<usize>::with_dense_limit(usize::MAX);
+    assert_eq!(set.dense_limit(), MAX_DENSE_LIMIT);
+
+    // Would need a multi-gigabyte bitmap; goes to the hash table instead
+    assert!(set.insert(usize::MAX / 2));
+    assert!(!set.is_dense());
+    assert!(set.contains(&(usize::MAX / 2)));
+}
@@ -0,0 +1,306 @@
+use crate::{DefaultHashBuilder, HashSet};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+
+const INITIAL_BUCKETS: usize = 16;
+
+/// A set that keeps every element in one contiguous allocation instead of a
+/// `Vec` per bucket, for better locality on scans.
+///
+/// Bucket `i` owns the run `slots[start..start + len]` described by
+/// `spans[i]`; runs are always fully occupied. Inserting into the bucket
+/// whose run ends the vector just pushes. Any other bucket is first moved
+/// to the end, leaving a hole behind. Removal swaps the element with the
+/// last of its run, which leaves a hole there. Holes are reclaimed by
+/// `compact`, which runs automatically once they outnumber live elements
+/// and on every resize.
+pub struct FlatHashSet<T, S = DefaultHashBuilder> {
+    slots: Vec<Option<T>>,
+    spans: Vec<(usize, usize)>,
+    size: usize,
+    hash_builder: S,
+}
+
+impl<T: Hash + Eq> Default for FlatHashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq> FlatHashSet<T> {
+    pub fn new() -> Self {
+        Self::with_hasher(DefaultHashBuilder::default())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> FromIterator<T> for FlatHashSet<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::with_hasher(S::default());
+
+        for item in iter {
+            set.insert(item);
+        }
+
+        set
+    }
+}
+
+impl<T, S> FlatHashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_buckets_and_hasher(INITIAL_BUCKETS, hash_builder)
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let mut set =
+            Self::with_buckets_and_hasher(HashSet::<T>::required_buckets(capacity), hash_builder);
+        set.slots.reserve(capacity);
+        set
+    }
+
+    fn with_buckets_and_hasher(bucket_count: usize, hash_builder: S) -> Self {
+        Self {
+            slots: Vec::new(),
+            spans: vec![(0, 0); bucket_count],
+            size: 0,
+            hash_builder,
+        }
+    }
+
+    fn bucket_index<Q: Hash + ?Sized>(&self, value: &Q) -> usize {
+        (self.hash_builder.hash_one(value) as usize) % self.spans.len()
+    }
+
+    fn bucket(&self, index: usize) -> &[Option<T>] {
+        let (start, len) = self.spans[index];
+        &self.slots[start..start + len]
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+
+        if (self.size + 1) * 4 > self.spans.len() * 3 {
+            let target = self.spans.len().checked_mul(2);
+            self.rebuild(target.expect("HashSet capacity overflow"));
+        }
+
+        let index = self.bucket_index(&value);
+        let (start, len) = self.spans[index];
+
+        if start + len != self.slots.len() {
+            // Move the run to the end so it can grow in place, swapping it
+            // with fresh holes rather than collecting it first
+            let end = self.slots.len();
+            self.slots.resize_with(end + len, || None);
+            let (head, tail) = self.slots.split_at_mut(end);
+            head[start..start + len].swap_with_slice(tail);
+            self.spans[index].0 = end;
+        }
+
+        self.slots.push(Some(value));
+        self.spans[index].1 += 1;
+        self.size += 1;
+        self.compact_if_sparse();
+
+        true
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.bucket(self.bucket_index(value))
+            .iter()
+            .any(|slot| slot.as_ref().is_some_and(|v| v.borrow() == value))
+    }
+
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.bucket(self.bucket_index(value))
+            .iter()
+            .flatten()
+            .find(|v| (*v).borrow() == value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let index = self.bucket_index(value);
+        let (start, len) = self.spans[index];
+        let run = &mut self.slots[start..start + len];
+
+        let Some(pos) = run
+            .iter()
+            .position(|slot| slot.as_ref().is_some_and(|v| v.borrow() == value))
+        else {
+            return false;
+        };
+
+        run.swap(pos, len - 1);
+        run[len - 1] = None;
+        self.spans[index].1 -= 1;
+        self.size -= 1;
+        self.compact_if_sparse();
+
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Slots no bucket currently owns.
+    pub fn holes(&self) -> usize {
+        self.slots.len() - self.size
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.spans.fill((0, 0));
+        self.size = 0;
+    }
+
+    /// Scans the flat storage front to back, skipping holes.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().flatten()
+    }
+
+    /// Repacks the runs back to back in bucket order, removing every hole.
+    pub fn compact(&mut self) {
+        self.rebuild(self.spans.len());
+    }
+
+    fn compact_if_sparse(&mut self) {
+        if self.holes() > self.size.max(INITIAL_BUCKETS) {
+            self.compact();
+        }
+    }
+
+    // Lays the elements out as a counting sort by bucket over
+    // `bucket_count` buckets, which also serves as the resize path.
+    fn rebuild(&mut self, bucket_count: usize) {
+        let values: Vec<(usize, T)> = self
+            .slots
+            .drain(..)
+            .flatten()
+            .map(|v| ((self.hash_builder.hash_one(&v) as usize) % bucket_count, v))
+            .collect();
+
+        let mut spans = vec![(0, 0); bucket_count];
+        for (index, _) in &values {
+            spans[*index].1 += 1;
+        }
+
+        let mut start = 0;
+        for span in &mut spans {
+            span.0 = start;
+            start += span.1;
+        }
+
+        let mut next: Vec<usize> = spans.iter().map(|span| span.0).collect();
+        self.slots.resize_with(values.len(), || None);
+        for (index, value) in values {
+            self.slots[next[index]] = Some(value);
+            next[index] += 1;
+        }
+
+        self.spans = spans;
+        debug_assert_eq!(self.slots.len(), self.size);
+    }
+}
+
+#[test]
+fn test_flat_insert_remove() {
+    let mut set = FlatHashSet::new();
+    for i in 0..1000 {
+        assert!(set.insert(i));
+    }
+    assert!(!set.insert(500));
+    assert_eq!(set.len(), 1000);
+    assert!((0..1000).all(|i| set.contains(&i)));
+    assert!(!set.contains(&1000));
+
+    for i in (0..1000).step_by(2) {
+        assert!(set.remove(&i));
+    }
+    assert!(!set.remove(&0));
+    assert_eq!(set.len(), 500);
+    for i in 0..1000 {
+        assert_eq!(set.contains(&i), i % 2 == 1);
+    }
+
+    let mut seen: Vec<i32> = set.iter().copied().collect();
+    seen.sort();
+    assert_eq!(seen, (1..1000).step_by(2).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_flat_holes_and_compaction() {
+    let mut set = FlatHashSet::with_capacity(100);
+    for i in 0..70 {
+        set.insert(i);
+    }
+    assert!(set.holes() > 0); // Runs moved to the end left gaps
+
+    set.compact();
+    assert_eq!(set.holes(), 0);
+    assert!((0..70).all(|i| set.contains(&i)));
+
+    // Removals never let holes outgrow the live elements for long
+    for i in 0..60 {
+        set.remove(&i);
+        assert!(set.holes() <= set.len().max(INITIAL_BUCKETS));
+    }
+    assert!((60..70).all(|i| set.contains(&i)));
+    assert_eq!(set.get(&65), Some(&65));
+}
+
+#[test]
+fn test_flat_matches_hash_set() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(9);
+    let mut flat = FlatHashSet::new();
+    let mut reference = HashSet::new();
+
+    for _ in 0..20_000 {
+        let value: u16 = rng.gen_range(0..2000);
+        if rng.gen_bool(0.6) {
+            assert_eq!(flat.insert(value), reference.insert(value));
+        } else {
+            assert_eq!(flat.remove(&value), reference.remove(&value));
+        }
+    }
+
+    assert_eq!(flat.len(), reference.len());
+    assert!(reference.iter().all(|v| flat.contains(v)));
+    assert_eq!(flat.iter().count(), reference.len());
+
+    flat.clear();
+    assert!(flat.is_empty());
+    assert_eq!(flat.iter().count(), 0);
+}
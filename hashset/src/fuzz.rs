@@ -0,0 +1,78 @@
+//! Differential harness for fuzzing: replays a sequence of operations against
+//! both this crate's `HashSet` and `std::collections::HashSet`, panicking as
+//! soon as their results or contents diverge.
+
+use crate::HashSet;
+use std::collections::HashSet as StdHashSet;
+use std::fmt;
+use std::hash::Hash;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<T> {
+    Insert(T),
+    Remove(T),
+    Contains(T),
+    Clear,
+    Resize,
+}
+
+pub fn apply_ops<T: Hash + Eq + Clone + fmt::Debug>(ops: &[Op<T>]) {
+    let mut set = HashSet::new();
+    let mut reference = StdHashSet::new();
+
+    for (step, op) in ops.iter().enumerate() {
+        match op {
+            Op::Insert(v) => assert_eq!(
+                set.insert(v.clone()),
+                reference.insert(v.clone()),
+                "step {step}: {op:?}"
+            ),
+            Op::Remove(v) => assert_eq!(set.remove(v), reference.remove(v), "step {step}: {op:?}"),
+            Op::Contains(v) => {
+                assert_eq!(
+                    set.contains(v),
+                    reference.contains(v),
+                    "step {step}: {op:?}"
+                )
+            }
+            Op::Clear => {
+                set.clear();
+                reference.clear();
+            }
+            Op::Resize => set.resize(),
+        }
+
+        assert_eq!(set.len(), reference.len(), "step {step}: {op:?}");
+        assert!(
+            reference.iter().all(|v| set.contains(v)),
+            "step {step}: {op:?}"
+        );
+        assert!(
+            set.iter().all(|v| reference.contains(v)),
+            "step {step}: {op:?}"
+        );
+    }
+}
+
+#[test]
+fn test_apply_ops_parity() {
+    use Op::*;
+
+    apply_ops(&[
+        Insert(1),
+        Insert(2),
+        Insert(1),
+        Contains(1),
+        Contains(3),
+        Resize,
+        Remove(2),
+        Remove(2),
+        Contains(2),
+        Insert(3),
+        Clear,
+        Contains(1),
+        Insert(4),
+        Resize,
+        Contains(4),
+    ]);
+}
@@ -0,0 +1,98 @@
+use crate::HashSet;
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+/// An approximately-LRU set built from two generations. New and recently
+/// touched elements live in the young generation; `evict_old` drops
+/// whatever is left in the old one and ages the young generation into its
+/// place. An element therefore survives as long as it is touched at least
+/// once between consecutive evictions (second-chance, without per-access
+/// timestamps).
+pub struct GenerationalHashSet<T> {
+    young: HashSet<T>,
+    old: HashSet<T>,
+}
+
+impl<T: Hash + Eq> Default for GenerationalHashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> GenerationalHashSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new() -> Self {
+        Self {
+            young: HashSet::new(),
+            old: HashSet::new(),
+        }
+    }
+
+    /// Inserts into the young generation, returning `false` if `value` was
+    /// already present in either generation (it is promoted either way).
+    pub fn insert(&mut self, value: T) -> bool {
+        let hash = self.old.hash_of(&value);
+        let was_old = self.old.take_hashed(hash, |v| v == &value).is_some();
+
+        self.young.insert(value) && !was_old
+    }
+
+    /// Checks membership, promoting a hit from the old generation so it
+    /// survives the next `evict_old`.
+    pub fn contains<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        if self.young.contains(value) {
+            return true;
+        }
+
+        match self.old.take(value) {
+            Some(promoted) => {
+                self.young.insert(promoted);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drops every element not touched since the previous call and returns
+    /// how many were dropped.
+    pub fn evict_old(&mut self) -> usize {
+        let evicted = self.old.len();
+        self.old = std::mem::take(&mut self.young);
+
+        evicted
+    }
+
+    pub fn len(&self) -> usize {
+        self.young.len() + self.old.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[test]
+fn test_generational_eviction() {
+    let mut set = GenerationalHashSet::new();
+    for i in 0..10 {
+        assert!(set.insert(i));
+    }
+    assert!(!set.insert(3));
+
+    assert_eq!(set.evict_old(), 0); // Everything just ages
+    assert!(set.contains(&2));
+    assert!(set.contains(&5));
+    assert!(!set.insert(7)); // Re-inserting touches too
+
+    assert_eq!(set.evict_old(), 7);
+    assert_eq!(set.len(), 3);
+    for i in 0..10 {
+        assert_eq!(set.contains(&i), [2, 5, 7].contains(&i));
+    }
+}
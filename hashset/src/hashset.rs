@@ -1,42 +1,310 @@
-use crate::Iter;
+use crate::codec::{Decode, DecodeError, Encode};
+use crate::iter::{Difference, Drain, Intersection, IntoIter, SymmetricDifference, Union};
+use crate::{ConstantTimeEq, Iter};
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::any::Any;
 use std::borrow::Borrow;
+use std::cell::RefCell;
 use std::collections::hash_map::DefaultHasher;
 use std::fmt;
-use std::hash::{Hash, Hasher};
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use std::ops::{BitOr, RangeInclusive};
+use std::sync::OnceLock;
+#[cfg(feature = "query_cache")]
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
-pub struct HashSet<T> {
+/// The hasher used when none is given: `DefaultHasher` with fixed keys, so
+/// layouts and iteration order are reproducible between runs.
+pub type DefaultHashBuilder = BuildHasherDefault<DefaultHasher>;
+
+/// `DefaultHasher` keyed by a 64-bit seed, so different seeds give
+/// unrelated layouts while a fixed seed stays reproducible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeededState {
+    seed: u64,
+}
+
+impl SeededState {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BuildHasher for SeededState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.seed);
+        hasher
+    }
+}
+
+pub struct HashSet<T, S = DefaultHashBuilder> {
     buckets: Vec<Vec<T>>,
     size: usize,
+    resize_count: usize,
+    max_probe: usize,
+    hash_builder: S,
+    bulk: Option<Vec<T>>,
+    // `(old, new)` bucket counts of each resize, kept only while a wrapper
+    // such as `ConfiguredHashSet` has asked for them.
+    resize_log: Option<Vec<(usize, usize)>>,
+    fingerprint: OnceLock<u64>,
+    #[cfg(feature = "query_cache")]
+    last_query: QueryCache,
+}
+
+// Where the last successful `contains` found its match. Atomics rather than
+// `Cell`s keep the set `Sync`; since a hit is confirmed by comparing the
+// element at that spot, a torn read between the two fields only costs a
+// cache miss, never a wrong answer.
+#[cfg(feature = "query_cache")]
+struct QueryCache {
+    hash: AtomicU64,
+    pos: AtomicUsize,
+}
+
+#[cfg(feature = "query_cache")]
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self {
+            hash: AtomicU64::new(0),
+            pos: AtomicUsize::new(usize::MAX),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsertOutcome {
+    pub inserted: bool,
+    pub resized: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetDiff<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+}
+
+/// The requested capacity needs more buckets than can be allocated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryReserveError;
+
+impl fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HashSet capacity overflow")
+    }
+}
+
+impl std::error::Error for TryReserveError {}
+
+/// A bounded lookup ran out of probes before it could answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExceeded;
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("lookup exceeded its probe budget")
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// What `HashSet::retain_with_policy` does with spare capacity afterwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShrinkPolicy {
+    Never,
+    Fit,
+    /// Shrink but keep room for this fraction of extra elements, as in
+    /// `shrink_to_fit_with_headroom`.
+    Headroom(f64),
+}
+
+/// How a lookup proceeds for one key, from `HashSet::explain_lookup`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LookupExplanation {
+    pub hash: u64,
+    pub bucket: usize,
+    pub chain_len: usize,
+    /// Index of the match within the bucket's chain, if the key is present.
+    pub position: Option<usize>,
+}
+
+/// Keys collected while iterating a set, to be removed afterwards with
+/// `HashSet::apply_removals` once the shared borrow has ended.
+#[derive(Debug, Clone)]
+pub struct RemovalBatch<T> {
+    keys: Vec<T>,
+}
+
+impl<T> RemovalBatch<T> {
+    pub fn push(&mut self, key: T) {
+        self.keys.push(key);
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// A long-lived handle to an element, returned by `HashSet::pin_lookup`.
+///
+/// A plain `&T` can't be held across inserts, since a resize moves every
+/// element (and the borrow checker would forbid the inserts anyway). The
+/// handle instead owns a copy of the key and re-hashes it on each `resolve`,
+/// so it keeps finding the element however the table has changed. It can't
+/// implement `Deref` because resolving needs the set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinnedRef<T> {
+    key: T,
+}
+
+impl<T: Hash + Eq> PinnedRef<T> {
+    /// Returns the element in `set`, or `None` if it has since been removed.
+    pub fn resolve<'a, S: BuildHasher>(&self, set: &'a HashSet<T, S>) -> Option<&'a T> {
+        set.get(&self.key)
+    }
+}
+
+/// Chain-length statistics for a set's buckets, built by
+/// `HashSet::bucket_stats`. Fields are private so new metrics can be added
+/// without breaking callers.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketStats {
+    longest_chain: usize,
+    empty_buckets: usize,
+    mean: f64,
+    variance: f64,
+}
+
+impl BucketStats {
+    pub fn longest_chain(&self) -> usize {
+        self.longest_chain
+    }
+
+    pub fn empty_buckets(&self) -> usize {
+        self.empty_buckets
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    pub fn variance(&self) -> f64 {
+        self.variance
+    }
+}
+
+const INITIAL_BUCKETS: usize = 16;
+const DEFAULT_MAX_PROBE: usize = 32;
+// Long-chain resizes stop once there are this many buckets per element, since
+// values with identical hashes can never be split apart by growing.
+const MAX_PROBE_GROWTH: usize = 4;
+
+thread_local! {
+    static SCRATCH: RefCell<Vec<Box<dyn Any>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Binary-search membership test over a slice from `into_sorted_boxed`.
+pub fn contains_sorted<T: Ord>(slice: &[T], value: &T) -> bool {
+    slice.binary_search(value).is_ok()
+}
+
+/// Lazily yields the first occurrence of each distinct element of `iter`,
+/// in input order. Only the distinct values seen so far are kept.
+pub fn dedup_iter<T, I>(iter: I) -> impl Iterator<Item = T>
+where
+    T: Hash + Eq + Clone,
+    I: IntoIterator<Item = T>,
+{
+    let mut seen = HashSet::new();
+
+    iter.into_iter()
+        .filter(move |value| !seen.contains(value) && seen.insert(value.clone()))
+}
+
+// Allocations are capped at `isize::MAX` bytes
+fn max_buckets<T>() -> usize {
+    isize::MAX as usize / std::mem::size_of::<Vec<T>>()
 }
 
 fn create_buckets<T>(size: usize) -> Vec<Vec<T>> {
     std::iter::repeat_with(Vec::new).take(size).collect()
 }
 
-impl<T: Hash + Eq + fmt::Debug> fmt::Debug for HashSet<T> {
+impl<T: Hash + Eq + fmt::Debug, S: BuildHasher> fmt::Debug for HashSet<T, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_set().entries(self.iter()).finish()
     }
 }
 
-impl<T: Hash + Eq + Clone> Clone for HashSet<T> {
+// No `Hash + Eq` bound, so `#[derive(Clone)]` on a struct holding a
+// `HashSet<T>` only needs `T: Clone`.
+impl<T: Clone, S: Clone> Clone for HashSet<T, S> {
     fn clone(&self) -> Self {
         Self {
             buckets: self.buckets.clone(),
             size: self.size,
+            resize_count: self.resize_count,
+            max_probe: self.max_probe,
+            hash_builder: self.hash_builder.clone(),
+            bulk: self.bulk.clone(),
+            resize_log: self.resize_log.clone(),
+            fingerprint: self.fingerprint.clone(),
+            #[cfg(feature = "query_cache")]
+            last_query: QueryCache::default(),
         }
     }
 }
 
-impl<T: Hash + Eq> Default for HashSet<T> {
+impl<T: Hash + Eq, S: BuildHasher> PartialEq for HashSet<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        // Only use fingerprints that are already cached; computing one just
+        // for this comparison would cost as much as the scan itself.
+        if let (Some(a), Some(b)) = (self.fingerprint.get(), other.fingerprint.get())
+            && a != b
+        {
+            return false;
+        }
+
+        self.iter().all(|v| other.contains(v))
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> Eq for HashSet<T, S> {}
+
+impl<T: Hash + Eq + Clone, S: BuildHasher + Clone> BitOr for &HashSet<T, S> {
+    type Output = HashSet<T, S>;
+
+    fn bitor(self, other: Self) -> HashSet<T, S> {
+        self.union_set(other)
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> Default for HashSet<T, S> {
     fn default() -> Self {
-        Self::new()
+        Self::with_hasher(S::default())
     }
 }
 
-impl<T: Hash + Eq> FromIterator<T> for HashSet<T> {
+impl<T: Hash + Eq, S: BuildHasher + Default> FromIterator<T> for HashSet<T, S> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut set = Self::new();
+        let mut set = Self::default();
 
         for item in iter {
             set.insert(item);
@@ -46,174 +314,1714 @@ impl<T: Hash + Eq> FromIterator<T> for HashSet<T> {
     }
 }
 
+// Reserves for the whole input: an upper bound, since duplicates collapse.
+impl<T: Hash + Eq, S: BuildHasher + Default> From<Vec<T>> for HashSet<T, S> {
+    fn from(vec: Vec<T>) -> Self {
+        let mut set = Self::with_capacity_and_hasher(vec.len(), S::default());
+
+        for item in vec {
+            set.insert(item);
+        }
+
+        set
+    }
+}
+
+// Reserves for the iterator's lower bound first; `reserve` only ever grows,
+// so extending a large set with a few items leaves it untouched. During a
+// bulk load the items are only staged, and `end_bulk` does the sizing.
+impl<T: Hash + Eq, S: BuildHasher> Extend<T> for HashSet<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        if self.bulk.is_none() {
+            self.reserve(iter.size_hint().0);
+        }
+
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<'a, T: Hash + Eq + Copy + 'a, S: BuildHasher> Extend<&'a T> for HashSet<T, S> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+// Flushes a pending bulk load first, so staged values are yielded too
+// (once each).
+impl<T: Hash + Eq, S: BuildHasher> IntoIterator for HashSet<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> IntoIter<T> {
+        self.end_bulk();
+
+        IntoIter {
+            inner: self.buckets.into_iter().flatten(),
+        }
+    }
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher> IntoIterator for &'a HashSet<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
 impl<T> HashSet<T>
 where
     T: Hash + Eq,
 {
     pub fn new() -> Self {
-        Self {
-            buckets: create_buckets::<T>(16),
-            size: 0,
-        }
+        Self::with_hasher(DefaultHashBuilder::default())
     }
 
-    fn hash(&self, value: &T) -> usize {
-        let mut hasher = DefaultHasher::new();
-        value.hash(&mut hasher);
-        (hasher.finish() as usize) % self.buckets.len()
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
     }
 
-    pub fn insert(&mut self, value: T) -> bool {
-        if (self.size + 1) * 4 > self.buckets.len() * 3 {
-            self.resize();
-        }
+    /// Returns the bucket count `with_capacity(element_capacity)` allocates:
+    /// the smallest count that holds `element_capacity` elements under the
+    /// 0.75 load factor, never below the default of 16.
+    ///
+    /// Panics if the bucket array could not be allocated on this target; see
+    /// `try_required_buckets`.
+    pub fn required_buckets(element_capacity: usize) -> usize {
+        Self::try_required_buckets(element_capacity).expect("HashSet capacity overflow")
+    }
 
-        let index = self.hash(&value);
-        let bucket = &mut self.buckets[index];
+    pub fn try_required_buckets(element_capacity: usize) -> Result<usize, TryReserveError> {
+        let buckets = element_capacity
+            .checked_mul(4)
+            .ok_or(TryReserveError)?
+            .div_ceil(3)
+            .max(INITIAL_BUCKETS);
 
-        if bucket.iter().any(|v| v == &value) {
-            return false;
+        if buckets > max_buckets::<T>() {
+            return Err(TryReserveError);
         }
 
-        bucket.push(value);
-        self.size += 1;
-
-        true
+        Ok(buckets)
     }
 
-    pub fn contains<Q>(&self, value: &Q) -> bool
+    pub fn from_bytes(data: &[u8]) -> Result<Self, DecodeError>
     where
-        Q: Hash + Eq + ?Sized,
-        T: Borrow<Q>,
+        T: Decode,
     {
-        let index = {
-            let mut hasher = DefaultHasher::new();
-            value.hash(&mut hasher);
-            (hasher.finish() as usize) % self.buckets.len()
+        let mut input = data;
+        let count = u64::decode(&mut input)?;
+        // A count the remaining bytes can't hold is rejected before anything
+        // is reserved. Without a known minimum width there is no such bound,
+        // so the set just grows as elements decode.
+        let capacity = match T::MIN_ENCODED_LEN {
+            0 => 0,
+            width => {
+                if count > (input.len() / width) as u64 {
+                    return Err(DecodeError::UnexpectedEof);
+                }
+                count as usize
+            }
         };
-        self.buckets[index].iter().any(|v| v.borrow() == value)
+        let mut set = Self::with_capacity(capacity);
+
+        for _ in 0..count {
+            set.insert(T::decode(&mut input)?);
+        }
+
+        if !input.is_empty() {
+            return Err(DecodeError::TrailingBytes(input.len()));
+        }
+
+        Ok(set)
     }
 
-    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    /// Deduplicates `vec`, returning the set along with the rejected
+    /// duplicates in input order (the first occurrence is the one kept).
+    pub fn from_vec_reporting(vec: Vec<T>) -> (Self, Vec<T>) {
+        let mut set = Self::with_capacity(vec.len());
+        let mut duplicates = Vec::new();
+
+        for value in vec {
+            let hash = set.hash_of(&value);
+
+            if set.find_hashed(hash, |v| v == &value).is_some() {
+                duplicates.push(value);
+            } else {
+                set.push_new(hash, value);
+            }
+        }
+
+        (set, duplicates)
+    }
+
+    /// Collects `iter`, deciding between equal elements with `better`:
+    /// `better(existing, incoming)` returning `true` replaces the stored
+    /// element with the incoming one. `|_, _| true` is last-wins and
+    /// `|_, _| false` first-wins.
+    pub fn from_iter_by<I, F>(iter: I, mut better: F) -> Self
     where
-        Q: Hash + Eq + ?Sized,
-        T: Borrow<Q>,
+        I: IntoIterator<Item = T>,
+        F: FnMut(&T, &T) -> bool,
     {
-        let index = {
-            let mut hasher = DefaultHasher::new();
-            value.hash(&mut hasher);
-            (hasher.finish() as usize) % self.buckets.len()
-        };
+        let mut set = Self::new();
 
-        let bucket = &mut self.buckets[index];
+        for value in iter {
+            let hash = set.hash_of(&value);
+            let index = (hash as usize) % set.buckets.len();
 
-        if let Some(pos) = bucket.iter().position(|v| v.borrow() == value) {
-            bucket.remove(pos);
-            self.size -= 1;
-            return true;
-        } else {
-            false
+            match set.buckets[index].iter().position(|v| v == &value) {
+                Some(pos) => {
+                    if better(&set.buckets[index][pos], &value) {
+                        set.buckets[index][pos] = value;
+                    }
+                }
+                None => {
+                    set.push_new(hash, value);
+                }
+            }
         }
-    }
 
-    pub fn len(&self) -> usize {
-        self.size
+        set
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.size == 0
-    }
+    /// Merges shards into one set. When every shard has the same bucket
+    /// count, an element already sits at the right index, so buckets are
+    /// concatenated in place (deduplicating only where two shards share a
+    /// bucket) and nothing is rehashed. Otherwise the shards are re-inserted
+    /// into the first one. Values a shard still has staged from
+    /// `begin_bulk` are flushed first.
+    pub fn concat_shards(shards: Vec<HashSet<T>>) -> Self {
+        let mut shards = shards.into_iter().map(|mut shard| {
+            shard.end_bulk();
+            shard
+        });
+        let Some(mut merged) = shards.next() else {
+            return Self::new();
+        };
+        let rest: Vec<HashSet<T>> = shards.collect();
 
-    pub fn capacity(&self) -> usize {
-        self.buckets.len()
-    }
+        if rest
+            .iter()
+            .any(|shard| shard.buckets.len() != merged.buckets.len())
+        {
+            merged.reserve(rest.iter().map(HashSet::len).sum());
+            for shard in rest {
+                merged.extend(shard);
+            }
+            return merged;
+        }
 
-    pub fn clear(&mut self) {
-        for bucket in &mut self.buckets {
-            bucket.clear();
+        for shard in rest {
+            for (into, from) in merged.buckets.iter_mut().zip(shard.buckets) {
+                if into.is_empty() {
+                    merged.size += from.len();
+                    *into = from;
+                    continue;
+                }
+
+                for value in from {
+                    if !into.contains(&value) {
+                        into.push(value);
+                        merged.size += 1;
+                    }
+                }
+            }
         }
-        self.size = 0
+
+        merged.invalidate_caches();
+        merged.debug_check_size();
+
+        merged
     }
 
-    fn resize(&mut self) {
-        let new_capacity = self.buckets.len() * 2;
-        let mut new_buckets = create_buckets::<T>(new_capacity);
+    /// Builds the union of two ascending iterators. Merging them puts equal
+    /// values next to each other, so repeats are dropped by comparing with
+    /// the previous value instead of being hashed. Unsorted input still
+    /// produces a correct set, just without that saving.
+    pub fn union_sorted<I, J>(a: I, b: J) -> Self
+    where
+        T: Ord,
+        I: IntoIterator<Item = T>,
+        J: IntoIterator<Item = T>,
+    {
+        let mut a = a.into_iter().peekable();
+        let mut b = b.into_iter().peekable();
+        let mut set = Self::with_capacity(a.size_hint().0.max(b.size_hint().0));
+        let mut pending: Option<T> = None;
 
-        for bucket in &mut self.buckets {
-            for value in std::mem::take(bucket) {
-                let mut hasher = DefaultHasher::new();
-                value.hash(&mut hasher);
-                let new_index = (hasher.finish() as usize) % new_capacity;
-                new_buckets[new_index].push(value);
+        loop {
+            let next = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) if x <= y => a.next(),
+                (Some(_), Some(_)) => b.next(),
+                (Some(_), None) => a.next(),
+                (None, _) => b.next(),
+            };
+
+            let Some(value) = next else {
+                break;
+            };
+
+            if pending.as_ref() != Some(&value)
+                && let Some(previous) = pending.replace(value)
+            {
+                set.insert(previous);
             }
         }
 
-        self.buckets = new_buckets
+        if let Some(last) = pending {
+            set.insert(last);
+        }
+
+        set
     }
 
-    pub fn iter(&self) -> Iter<'_, T> {
-        let mut bucket_iter = self.buckets.iter();
-        let current_bucket = bucket_iter.next().map(|b| b.iter());
+    /// Takes a cleared set from this thread's scratch pool, reusing the
+    /// bucket allocation of the last set handed back via `return_scratch`.
+    ///
+    /// The pool is a static thread-local holding at most one set per element
+    /// type, so this trades that retained memory for fewer allocations when
+    /// temporary sets are built over and over.
+    pub fn take_scratch() -> Self
+    where
+        T: 'static,
+    {
+        SCRATCH.with(|pool| {
+            let mut pool = pool.borrow_mut();
 
-        Iter {
-            bucket_iter,
-            current_bucket,
-        }
+            match pool.iter().position(|set| set.is::<Self>()) {
+                Some(pos) => *pool.swap_remove(pos).downcast::<Self>().unwrap(),
+                None => Self::new(),
+            }
+        })
     }
-}
 
-#[test]
-fn test_insert_and_contains() {
-    let mut set = HashSet::new();
-    assert!(set.insert(42));
-    assert!(!set.insert(42)); // Duplicate
-    assert!(set.contains(&42));
-    assert!(!set.contains(&99));
-}
-#[test]
-fn test_remove() {
-    let mut set = HashSet::new();
-    set.insert(42);
-    assert!(set.remove(&42));
-    assert!(!set.remove(&42)); // Already removed
-    assert!(!set.contains(&42));
-}
+    /// Hands the set back to this thread's pool, cleared. A pending bulk
+    /// load is discarded along with the elements, so the next
+    /// `take_scratch` caller never inherits staged values.
+    pub fn return_scratch(mut self)
+    where
+        T: 'static,
+    {
+        self.clear();
 
-#[test]
-fn test_size_tracking() {
-    let mut set = HashSet::new();
-    assert_eq!(set.len(), 0);
-    assert!(set.is_empty());
-    set.insert(1);
-    set.insert(2);
-    assert_eq!(set.len(), 2);
-    set.remove(&1);
-    assert_eq!(set.len(), 1);
-    set.clear();
-    assert_eq!(set.len(), 0);
-    assert_eq!(set.capacity(), 16); // Capacity unchanged
+        SCRATCH.with(|pool| {
+            let mut pool = pool.borrow_mut();
+
+            match pool.iter_mut().find(|set| set.is::<Self>()) {
+                Some(slot) => *slot = Box::new(self),
+                None => pool.push(Box::new(self)),
+            }
+        })
+    }
 }
 
-#[test]
-fn test_resize() {
-    let mut set = HashSet::new();
-    let initial_capacity = set.capacity();
-    // Insert enough to trigger resize (> 0.75 * 16 = 12)
-    for i in 0..20 {
-        set.insert(i);
+impl<T, S> HashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_buckets_and_hasher(INITIAL_BUCKETS, hash_builder)
     }
-    assert!(set.capacity() > initial_capacity);
-    assert_eq!(set.len(), 20);
-    // Verify all elements still present
-    for i in 0..20 {
-        assert!(set.contains(&i));
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self::with_buckets_and_hasher(HashSet::<T>::required_buckets(capacity), hash_builder)
     }
-}
 
-#[test]
-fn test_iterator() {
-    let mut set = HashSet::new();
-    set.insert(1);
+    fn with_buckets_and_hasher(bucket_count: usize, hash_builder: S) -> Self {
+        Self {
+            buckets: create_buckets::<T>(bucket_count),
+            size: 0,
+            resize_count: 0,
+            max_probe: DEFAULT_MAX_PROBE,
+            hash_builder,
+            bulk: None,
+            resize_log: None,
+            fingerprint: OnceLock::new(),
+            #[cfg(feature = "query_cache")]
+            last_query: QueryCache::default(),
+        }
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Replaces the hasher and re-indexes every element under it.
+    pub fn set_hasher(&mut self, hash_builder: S) {
+        self.hash_builder = hash_builder;
+        self.rehash_in_place();
+    }
+
+    /// Redistributes all elements with the current hasher without changing
+    /// the bucket count; the outer bucket array is reused.
+    pub fn rehash_in_place(&mut self) {
+        let indices = self.target_indices(self.buckets.len());
+        let values: Vec<T> = self.buckets.iter_mut().flat_map(std::mem::take).collect();
+
+        for (value, index) in values.into_iter().zip(indices) {
+            self.buckets[index].push(value);
+        }
+
+        self.forget_query();
+        self.debug_check_size();
+    }
+
+    pub fn max_probe(&self) -> usize {
+        self.max_probe
+    }
+
+    /// Sets the chain length at which an insert grows the table even though
+    /// the load factor is healthy, bounding lookup cost under a weak hasher.
+    pub fn set_max_probe(&mut self, max_probe: usize) {
+        self.max_probe = max_probe;
+    }
+
+    pub(crate) fn hash_of<Q: Hash + ?Sized>(&self, value: &Q) -> u64 {
+        self.hash_builder.hash_one(value)
+    }
+
+    /// The hash this set's hasher gives `value`, for use with the
+    /// `_for_hash` probes when hashes are computed upstream.
+    pub fn raw_hash<Q: Hash + ?Sized>(&self, value: &Q) -> u64 {
+        self.hash_of(value)
+    }
+
+    /// Whether some element hashes to exactly `hash`. Hashes aren't cached,
+    /// so this rehashes the candidates in its bucket.
+    pub fn contains_any_with_hash(&self, hash: u64) -> bool {
+        self.candidates_for_hash(hash)
+            .iter()
+            .any(|v| self.hash_of(v) == hash)
+    }
+
+    /// The bucket `hash` maps to. It may hold elements with other hashes;
+    /// callers do the final `==` themselves.
+    pub fn candidates_for_hash(&self, hash: u64) -> &[T] {
+        &self.buckets[(hash as usize) % self.buckets.len()]
+    }
+
+    // Lookups by precomputed hash let wrapper sets find entries by a
+    // borrowed key or by a side field without needing `T: Borrow<Q>`.
+    pub(crate) fn find_hashed(&self, hash: u64, mut eq: impl FnMut(&T) -> bool) -> Option<&T> {
+        let index = (hash as usize) % self.buckets.len();
+        self.buckets[index].iter().find(|v| eq(v))
+    }
+
+    pub(crate) fn take_hashed(&mut self, hash: u64, eq: impl FnMut(&T) -> bool) -> Option<T> {
+        let index = (hash as usize) % self.buckets.len();
+        let pos = self.buckets[index].iter().position(eq)?;
+        self.size -= 1;
+        self.invalidate_caches();
+
+        Some(self.buckets[index].remove(pos))
+    }
+
+    fn bucket_index<Q: Hash + ?Sized>(&self, value: &Q) -> usize {
+        (self.hash_of(value) as usize) % self.buckets.len()
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        if let Some(staged) = &mut self.bulk {
+            staged.push(value);
+            return true;
+        }
+
+        let hash = self.hash_of(&value);
+
+        if self.find_hashed(hash, |v| v == &value).is_some() {
+            return false;
+        }
+
+        self.push_new(hash, value);
+
+        true
+    }
+
+    /// Returns the stored element equal to `value`, inserting `value` first
+    /// if absent. The hash is computed once and reused for the push; only a
+    /// resize forces the bucket index to be recomputed from it.
+    pub fn get_or_insert(&mut self, value: T) -> &T {
+        let hash = self.hash_of(&value);
+        let index = (hash as usize) % self.buckets.len();
+
+        let (index, pos) = match self.buckets[index].iter().position(|v| v == &value) {
+            Some(pos) => (index, pos),
+            None => self.push_new(hash, value),
+        };
+
+        &self.buckets[index][pos]
+    }
+
+    /// Looks up `key`, building and inserting an element with `f` on a miss.
+    /// The flag is `true` when `f` ran. The built value must be equal to
+    /// `key` (and hash the same), as with std's `get_or_insert_with`.
+    pub fn get_or_insert_with_status<Q, F>(&mut self, key: &Q, f: F) -> (&T, bool)
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+        F: FnOnce(&Q) -> T,
+    {
+        let hash = self.hash_of(key);
+        let index = (hash as usize) % self.buckets.len();
+
+        // Positions rather than references, so a resize can happen before
+        // the returned borrow is taken
+        let (index, pos, inserted) =
+            match self.buckets[index].iter().position(|v| v.borrow() == key) {
+                Some(pos) => (index, pos, false),
+                None => {
+                    let value = f(key);
+                    debug_assert!(value.borrow() == key, "built value must equal the key");
+                    let (index, pos) = self.push_new(hash, value);
+                    (index, pos, true)
+                }
+            };
+
+        (&self.buckets[index][pos], inserted)
+    }
+
+    pub fn get_or_insert_with<Q, F>(&mut self, key: &Q, f: F) -> &T
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+        F: FnOnce(&Q) -> T,
+    {
+        self.get_or_insert_with_status(key, f).0
+    }
+
+    // Pushes a value known to be absent, growing first if needed, and
+    // returns where it landed. Bypasses bulk staging; wrapper sets use it
+    // after a `find_hashed` miss to avoid hashing the value twice.
+    pub(crate) fn push_new(&mut self, hash: u64, value: T) -> (usize, usize) {
+        if (self.size + 1) * 4 > self.buckets.len() * 3 {
+            self.resize();
+        }
+
+        let mut index = (hash as usize) % self.buckets.len();
+
+        if self.buckets[index].len() >= self.max_probe
+            && self.buckets.len() < self.size * MAX_PROBE_GROWTH
+        {
+            self.resize();
+            index = (hash as usize) % self.buckets.len();
+        }
+
+        self.buckets[index].push(value);
+        self.size += 1;
+        self.invalidate_caches();
+
+        (index, self.buckets[index].len() - 1)
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional)
+            .expect("HashSet capacity overflow");
+    }
+
+    /// Like `reserve`, but reports an error instead of panicking when the
+    /// required bucket count does not fit in memory on this target.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let total = self.size.checked_add(additional).ok_or(TryReserveError)?;
+        let required = HashSet::<T>::try_required_buckets(total)?;
+
+        if required > self.buckets.len() {
+            self.resize_to(required);
+        }
+
+        Ok(())
+    }
+
+    /// Reserves for the share of `incoming` elements expected to be new,
+    /// e.g. `0.6` when 60% of ingested rows are unseen. The ratio is clamped
+    /// to `[0, 1]`.
+    pub fn reserve_estimated(&mut self, incoming: usize, expected_unique_ratio: f64) {
+        let ratio = expected_unique_ratio.clamp(0.0, 1.0);
+        self.reserve((incoming as f64 * ratio).ceil() as usize);
+    }
+
+    /// Like `reserve`, but also rounds the bucket count up to a power of two
+    /// (which `reserve` alone does not guarantee, e.g. after
+    /// `with_capacity`). Since growth doubles, the count stays a power of two
+    /// until the set is shrunk or reserved for again.
+    pub fn reserve_pow2(&mut self, additional: usize) {
+        let total = self.size.checked_add(additional);
+        let required = HashSet::<T>::required_buckets(total.expect("HashSet capacity overflow"));
+        let target = required
+            .max(self.buckets.len())
+            .checked_next_power_of_two()
+            .filter(|&target| target <= max_buckets::<T>())
+            .expect("HashSet capacity overflow");
+
+        if target != self.buckets.len() {
+            self.resize_to(target);
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to_fit_with_headroom(0.0);
+    }
+
+    /// Shrinks the table to hold `len() * (1 + headroom)` elements under the
+    /// load factor, so the next inserts don't immediately grow it again.
+    pub fn shrink_to_fit_with_headroom(&mut self, headroom: f64) {
+        let target = (self.size as f64 * (1.0 + headroom.max(0.0))).ceil() as usize;
+        // A headroom too large to allocate means there is nothing to shrink
+        if let Ok(required) = HashSet::<T>::try_required_buckets(target)
+            && required < self.buckets.len()
+        {
+            self.resize_to(required);
+        }
+    }
+
+    /// Inserts every element of `iter`, returning how many were new.
+    pub fn extend_counting<I: IntoIterator<Item = T>>(&mut self, iter: I) -> usize {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        let mut added = 0;
+
+        for value in iter {
+            if self.insert(value) {
+                added += 1;
+            }
+        }
+
+        added
+    }
+
+    /// Inserts a batch with at most one resize, returning how many elements
+    /// were new. The batch is collected first so its exact length is known
+    /// whatever its size hint says, and the table is sized for all of it
+    /// being new; duplicates leave headroom that `shrink_to_fit` can trim.
+    ///
+    /// During a bulk load the batch is staged instead, and the count is of
+    /// staged values, since duplicates aren't known until `end_bulk`.
+    pub fn insert_batch(&mut self, items: impl IntoIterator<Item = T>) -> usize {
+        if let Some(staged) = &mut self.bulk {
+            let before = staged.len();
+            staged.extend(items);
+            return staged.len() - before;
+        }
+
+        let items: Vec<T> = items.into_iter().collect();
+        self.reserve(items.len());
+
+        let before = self.size;
+        for value in items {
+            self.insert(value);
+        }
+
+        self.size - before
+    }
+
+    /// Starts a bulk load: until `end_bulk`, `insert` only appends to a
+    /// staging buffer, skipping the duplicate and resize checks and always
+    /// returning `true`. Lookups, `len` and iteration don't see staged values
+    /// during the session and must not be relied on until it ends.
+    pub fn begin_bulk(&mut self) {
+        if self.bulk.is_none() {
+            self.bulk = Some(Vec::new());
+        }
+    }
+
+    /// Ends a bulk load, sizing the table once for everything staged and
+    /// then inserting it, so at most one rehash happens for the whole load.
+    /// The `max_probe` growth trigger is suspended meanwhile; chains left
+    /// longer than it are dealt with by the next ordinary insert.
+    pub fn end_bulk(&mut self) {
+        let Some(staged) = self.bulk.take() else {
+            return;
+        };
+
+        self.reserve(staged.len());
+
+        let max_probe = std::mem::replace(&mut self.max_probe, usize::MAX);
+        for value in staged {
+            self.insert(value);
+        }
+        self.max_probe = max_probe;
+    }
+
+    /// Inserts `value`, also returning the length of the chain it sits in
+    /// afterwards. Consistently deep chains point to a weak hasher. Bypasses
+    /// bulk staging so the depth reflects the real bucket.
+    pub fn insert_with_depth(&mut self, value: T) -> (bool, usize) {
+        let hash = self.hash_of(&value);
+        let index = (hash as usize) % self.buckets.len();
+
+        if self.buckets[index].contains(&value) {
+            return (false, self.buckets[index].len());
+        }
+
+        let (index, _) = self.push_new(hash, value);
+
+        (true, self.buckets[index].len())
+    }
+
+    pub fn insert_profiled(&mut self, value: T) -> InsertOutcome {
+        let resizes_before = self.resize_count;
+        let inserted = self.insert(value);
+
+        InsertOutcome {
+            inserted,
+            resized: self.resize_count != resizes_before,
+        }
+    }
+
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let index = self.bucket_index(value);
+        self.buckets[index].iter().find(|v| (*v).borrow() == value)
+    }
+
+    /// Same as `get`; named for code ported from map indexing.
+    pub fn try_index<Q>(&self, value: &Q) -> Option<&T>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.get(value)
+    }
+
+    /// Returns the stored element equal to `value`, panicking if absent.
+    ///
+    /// This is what `set[&value]` would do, but `std::ops::Index` is not
+    /// implemented: an operator that panics on a miss is easy to reach for
+    /// by accident on a set, where absence is the common case.
+    pub fn index_unchecked<Q>(&self, value: &Q) -> &T
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.get(value).expect("value not present in HashSet")
+    }
+
+    /// Looks up every key, visiting buckets in index order for locality.
+    /// Results come back in the order of `keys`.
+    pub fn get_batch_sorted<Q>(&self, keys: &[Q]) -> Vec<Option<&T>>
+    where
+        Q: Hash + Eq,
+        T: Borrow<Q>,
+    {
+        let mut order: Vec<(usize, usize)> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (self.bucket_index(key), i))
+            .collect();
+        order.sort_unstable();
+
+        let mut results = vec![None; keys.len()];
+        for (bucket, i) in order {
+            results[i] = self.buckets[bucket]
+                .iter()
+                .find(|v| (*v).borrow() == &keys[i]);
+        }
+
+        results
+    }
+
+    pub fn pin_lookup<Q>(&self, value: &Q) -> Option<PinnedRef<T>>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q> + Clone,
+    {
+        self.get(value).map(|v| PinnedRef { key: v.clone() })
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.hash_of(value);
+        let index = (hash as usize) % self.buckets.len();
+
+        #[cfg(feature = "query_cache")]
+        if let Some(hit) = self.cached_query(hash, index, value) {
+            return hit;
+        }
+
+        let found = self.buckets[index].iter().position(|v| v.borrow() == value);
+
+        #[cfg(feature = "query_cache")]
+        if let Some(pos) = found {
+            self.last_query.hash.store(hash, Ordering::Relaxed);
+            self.last_query.pos.store(pos, Ordering::Relaxed);
+        }
+
+        found.is_some()
+    }
+
+    #[cfg(feature = "query_cache")]
+    fn cached_query<Q>(&self, hash: u64, index: usize, value: &Q) -> Option<bool>
+    where
+        Q: Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        if self.last_query.hash.load(Ordering::Relaxed) != hash {
+            return None;
+        }
+
+        let pos = self.last_query.pos.load(Ordering::Relaxed);
+        let hit = self.buckets[index].get(pos)?.borrow() == value;
+
+        hit.then_some(true)
+    }
+
+    /// Membership test that compares against at most `max_probes` chain
+    /// entries, failing with `BudgetExceeded` when the chain is longer and
+    /// the value was not among the entries checked, so deadline-sensitive
+    /// callers can defer instead of scanning on.
+    pub fn contains_bounded<Q>(&self, value: &Q, max_probes: usize) -> Result<bool, BudgetExceeded>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let bucket = &self.buckets[self.bucket_index(value)];
+
+        if bucket.iter().take(max_probes).any(|v| v.borrow() == value) {
+            Ok(true)
+        } else if bucket.len() <= max_probes {
+            Ok(false)
+        } else {
+            Err(BudgetExceeded)
+        }
+    }
+
+    /// Membership test that compares `value` against every element of its
+    /// bucket and ORs the results, so the matching position in the chain
+    /// isn't revealed by timing. Only the in-bucket scan is constant-time:
+    /// which bucket is probed, and its length, still depend on the hash.
+    pub fn constant_time_contains(&self, value: &T) -> bool
+    where
+        T: ConstantTimeEq,
+    {
+        let found = self.buckets[self.bucket_index(value)]
+            .iter()
+            .fold(0u8, |found, candidate| found | candidate.ct_eq(value));
+
+        std::hint::black_box(found) == 1
+    }
+
+    pub fn explain_lookup<Q>(&self, value: &Q) -> LookupExplanation
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.hash_of(value);
+        let bucket = (hash as usize) % self.buckets.len();
+        let chain = &self.buckets[bucket];
+
+        LookupExplanation {
+            hash,
+            bucket,
+            chain_len: chain.len(),
+            position: chain.iter().position(|v| v.borrow() == value),
+        }
+    }
+
+    /// The other elements sharing `value`'s bucket, i.e. what a lookup for
+    /// it has to compare against, or `None` if `value` isn't present.
+    pub fn bucket_neighbors<Q>(&self, value: &Q) -> Option<Vec<&T>>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let chain = &self.buckets[self.bucket_index(value)];
+        let pos = chain.iter().position(|v| v.borrow() == value)?;
+
+        Some(
+            chain
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != pos)
+                .map(|(_, v)| v)
+                .collect(),
+        )
+    }
+
+    /// Like `contains`, but moves a hit to the front of its bucket so hot
+    /// elements are found after fewer comparisons. Iteration order then
+    /// depends on the access history.
+    pub fn contains_mru<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let index = self.bucket_index(value);
+        let bucket = &mut self.buckets[index];
+
+        let Some(pos) = bucket.iter().position(|v| v.borrow() == value) else {
+            return false;
+        };
+
+        if pos > 0 {
+            bucket[..=pos].rotate_right(1);
+            self.forget_query();
+        }
+
+        true
+    }
+
+    pub fn contains_owned<Q>(&self, value: Q) -> bool
+    where
+        Q: Hash + Eq,
+        T: Borrow<Q>,
+    {
+        self.contains(&value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let index = self.bucket_index(value);
+
+        if let Some(pos) = self.buckets[index].iter().position(|v| v.borrow() == value) {
+            self.buckets[index].remove(pos);
+            self.size -= 1;
+            self.invalidate_caches();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes and returns the stored element equal to `value`.
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.hash_of(value);
+        self.take_hashed(hash, |v| v.borrow() == value)
+    }
+
+    /// Inserts `value` after removing every element of its bucket that
+    /// compares equal to it, returning those. With a lawful `Eq` that is at
+    /// most one element; a deliberately loose `Eq` can collapse several.
+    pub fn replace_all(&mut self, value: T) -> Vec<T> {
+        let hash = self.hash_of(&value);
+        let index = (hash as usize) % self.buckets.len();
+
+        let removed: Vec<T> = self.buckets[index]
+            .extract_if(.., |v| *v == value)
+            .collect();
+        self.size -= removed.len();
+        self.push_new(hash, value);
+
+        removed
+    }
+
+    /// Inserts `value`, returning the equal element it displaced, if any.
+    pub fn replace(&mut self, value: T) -> Option<T> {
+        let hash = self.hash_of(&value);
+        let index = (hash as usize) % self.buckets.len();
+
+        match self.buckets[index].iter().position(|v| v == &value) {
+            // Equal elements hash alike, so the fingerprint is unchanged
+            Some(pos) => Some(std::mem::replace(&mut self.buckets[index][pos], value)),
+            None => {
+                self.push_new(hash, value);
+                None
+            }
+        }
+    }
+
+    /// `extend` over a trait object, for callers that hold a boxed
+    /// iterator or want a single instantiation across iterator types.
+    pub fn extend_dyn(&mut self, iter: &mut dyn Iterator<Item = T>) {
+        self.extend(iter);
+    }
+
+    /// `remove_all` over a trait object of owned keys.
+    pub fn remove_all_dyn(&mut self, iter: &mut dyn Iterator<Item = T>) -> usize {
+        self.remove_each(iter, |set, key| set.remove(&key))
+    }
+
+    pub fn defer_removals(&self) -> RemovalBatch<T> {
+        RemovalBatch { keys: Vec::new() }
+    }
+
+    /// Removes every key in `batch`, returning how many were present.
+    pub fn apply_removals(&mut self, batch: RemovalBatch<T>) -> usize {
+        self.remove_all(&batch.keys)
+    }
+
+    /// Removes every key in `keys`, returning how many were present. Stops
+    /// pulling keys as soon as the set is empty, so a long key list against
+    /// a small set costs only as much as it has to.
+    pub fn remove_all<'a, Q, I>(&mut self, keys: I) -> usize
+    where
+        Q: Hash + Eq + ?Sized + 'a,
+        T: Borrow<Q>,
+        I: IntoIterator<Item = &'a Q>,
+    {
+        self.remove_each(keys, |set, key| set.remove(key))
+    }
+
+    // The loop behind `remove_all` and `remove_all_dyn`, which differ only
+    // in whether keys arrive borrowed or owned.
+    fn remove_each<K>(
+        &mut self,
+        keys: impl IntoIterator<Item = K>,
+        mut remove: impl FnMut(&mut Self, K) -> bool,
+    ) -> usize {
+        let mut removed = 0;
+
+        if self.is_empty() {
+            return removed;
+        }
+
+        for key in keys {
+            if remove(self, key) {
+                removed += 1;
+
+                if self.is_empty() {
+                    break;
+                }
+            }
+        }
+
+        removed
+    }
+
+    pub fn matching_indices<Q>(&self, keys: &[Q]) -> Vec<usize>
+    where
+        Q: Hash + Eq,
+        T: Borrow<Q>,
+    {
+        keys.iter()
+            .enumerate()
+            .filter(|(_, key)| self.contains(*key))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        for bucket in &mut self.buckets {
+            let before = bucket.len();
+            bucket.retain(|v| f(v));
+            self.size -= before - bucket.len();
+        }
+
+        self.invalidate_caches();
+
+        self.debug_check_size();
+    }
+
+    /// Like `retain`, but hands `f` a `&mut T` so survivors can be updated
+    /// in the same pass. Only fields that take no part in `Hash`/`Eq` may be
+    /// changed; touching the key leaves the element in the wrong bucket.
+    pub fn retain_transform<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        for bucket in &mut self.buckets {
+            let before = bucket.len();
+            bucket.retain_mut(|v| f(v));
+            self.size -= before - bucket.len();
+        }
+
+        self.invalidate_caches();
+
+        self.debug_check_size();
+    }
+
+    pub fn retain_with_policy<F: FnMut(&T) -> bool>(&mut self, f: F, policy: ShrinkPolicy) {
+        self.retain(f);
+
+        match policy {
+            ShrinkPolicy::Never => {}
+            ShrinkPolicy::Fit => self.shrink_to_fit(),
+            ShrinkPolicy::Headroom(headroom) => self.shrink_to_fit_with_headroom(headroom),
+        }
+    }
+
+    /// Removes every element for which `f` returns `true`, returning them
+    /// together with the number of elements kept. The bucket allocation is
+    /// left as is.
+    pub fn drain_filter_reporting<F: FnMut(&T) -> bool>(&mut self, mut f: F) -> (Vec<T>, usize) {
+        let mut drained = Vec::new();
+
+        for bucket in &mut self.buckets {
+            drained.extend(bucket.extract_if(.., |v| f(v)));
+        }
+
+        self.size -= drained.len();
+        self.invalidate_caches();
+        self.debug_check_size();
+
+        (drained, self.size)
+    }
+
+    /// Moves every element matching `pred` into `dest`, returning how many
+    /// left `self`. Elements `dest` already holds are simply dropped.
+    pub fn move_matching_to<F: FnMut(&T) -> bool>(
+        &mut self,
+        dest: &mut HashSet<T, S>,
+        pred: F,
+    ) -> usize {
+        let (moved, _) = self.drain_filter_reporting(pred);
+        let count = moved.len();
+
+        dest.reserve(count);
+        for value in moved {
+            dest.insert(value);
+        }
+
+        count
+    }
+
+    /// Like `retain`, but also shrinks every bucket `Vec` to fit afterwards,
+    /// reclaiming per-bucket slack without changing the bucket count.
+    pub fn retain_compacting<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        self.retain(f);
+
+        for bucket in &mut self.buckets {
+            bucket.shrink_to_fit();
+        }
+    }
+
+    /// Approximate heap bytes held by the bucket array and its chains.
+    pub fn memory_usage(&self) -> usize {
+        self.buckets.capacity() * std::mem::size_of::<Vec<T>>()
+            + self
+                .buckets
+                .iter()
+                .map(|b| b.capacity() * std::mem::size_of::<T>())
+                .sum::<usize>()
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Removes every element, including any staged by a pending bulk load,
+    /// and ends that bulk load.
+    pub fn clear(&mut self) {
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+        self.bulk = None;
+        self.size = 0;
+        self.invalidate_caches();
+    }
+
+    /// Swaps in `new`'s contents in O(1) and drops the old ones, e.g. to
+    /// publish a freshly built config set. The hasher comes along with the
+    /// buckets it laid out; settings such as `max_probe` stay as they were.
+    pub fn replace_contents(&mut self, mut new: HashSet<T, S>) {
+        std::mem::swap(&mut self.buckets, &mut new.buckets);
+        std::mem::swap(&mut self.size, &mut new.size);
+        std::mem::swap(&mut self.hash_builder, &mut new.hash_builder);
+        std::mem::swap(&mut self.bulk, &mut new.bulk);
+        self.invalidate_caches();
+    }
+
+    /// Empties the set, keeping the bucket allocation, and returns the
+    /// removed elements. A pending bulk load is flushed first, so staged
+    /// values are returned too and the bulk load ends.
+    pub fn drain(&mut self) -> Drain<T> {
+        self.end_bulk();
+        let values: Vec<T> = self.buckets.iter_mut().flat_map(|b| b.drain(..)).collect();
+        self.size = 0;
+        self.invalidate_caches();
+
+        Drain {
+            inner: values.into_iter(),
+        }
+    }
+
+    /// Empties the set, moving each element into `yes` if `pred` holds for
+    /// it and into `no` otherwise. Elements a destination already holds are
+    /// dropped. The bucket allocation is kept.
+    pub fn drain_route<F: FnMut(&T) -> bool>(
+        &mut self,
+        yes: &mut HashSet<T, S>,
+        no: &mut HashSet<T, S>,
+        mut pred: F,
+    ) {
+        for bucket in &mut self.buckets {
+            for value in bucket.drain(..) {
+                if pred(&value) {
+                    yes.insert(value);
+                } else {
+                    no.insert(value);
+                }
+            }
+        }
+
+        self.size = 0;
+        self.invalidate_caches();
+    }
+
+    // Doubles the bucket count, stopping at the largest allocatable array;
+    // past that, chains just grow longer.
+    pub(crate) fn resize(&mut self) {
+        let target = self.buckets.len().saturating_mul(2).min(max_buckets::<T>());
+
+        if target > self.buckets.len() {
+            self.resize_to(target);
+        }
+    }
+
+    fn resize_to(&mut self, new_capacity: usize) {
+        let old_capacity = self.buckets.len();
+        let indices = self.target_indices(new_capacity);
+        let mut new_buckets = create_buckets::<T>(new_capacity);
+
+        for (value, index) in self
+            .buckets
+            .iter_mut()
+            .flat_map(std::mem::take)
+            .zip(indices)
+        {
+            new_buckets[index].push(value);
+        }
+
+        self.buckets = new_buckets;
+        self.resize_count += 1;
+        self.forget_query();
+        self.debug_check_size();
+
+        if let Some(log) = &mut self.resize_log
+            && old_capacity != new_capacity
+        {
+            log.push((old_capacity, new_capacity));
+        }
+    }
+
+    /// Starts recording every bucket-count change for `take_resizes`.
+    pub(crate) fn record_resizes(&mut self) {
+        self.resize_log.get_or_insert_with(Vec::new);
+    }
+
+    pub(crate) fn take_resizes(&mut self) -> Vec<(usize, usize)> {
+        self.resize_log
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    // Every element's bucket under `bucket_count`, in iteration order. Both
+    // rehash paths compute these before moving anything, so a `Hash` impl
+    // that panics part way leaves the table as it was instead of dropping
+    // the elements already moved out.
+    fn target_indices(&self, bucket_count: usize) -> Vec<usize> {
+        self.iter()
+            .map(|v| (self.hash_builder.hash_one(v) as usize) % bucket_count)
+            .collect()
+    }
+
+    // Called by every path that adds or removes elements.
+    fn invalidate_caches(&mut self) {
+        self.fingerprint.take();
+        self.forget_query();
+    }
+
+    // Called on its own when elements only move between buckets, which
+    // leaves the fingerprint valid.
+    fn forget_query(&self) {
+        #[cfg(feature = "query_cache")]
+        self.last_query.pos.store(usize::MAX, Ordering::Relaxed);
+    }
+
+    // Full recount, so it only runs after operations that are O(n) anyway;
+    // the per-element paths only ever decrement `size` for a value they
+    // actually removed.
+    fn debug_check_size(&self) {
+        debug_assert_eq!(
+            self.size,
+            self.buckets.iter().map(Vec::len).sum::<usize>(),
+            "size out of sync with bucket contents"
+        );
+    }
+
+    pub fn resize_count(&self) -> usize {
+        self.resize_count
+    }
+
+    pub fn bucket_stats(&self) -> BucketStats {
+        let count = self.buckets.len() as f64;
+        let mean = self.size as f64 / count;
+        let variance = self
+            .buckets
+            .iter()
+            .map(|b| (b.len() as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count;
+
+        BucketStats {
+            longest_chain: self.buckets.iter().map(Vec::len).max().unwrap_or(0),
+            empty_buckets: self.buckets.iter().filter(|b| b.is_empty()).count(),
+            mean,
+            variance,
+        }
+    }
+
+    /// Read-only view of the internal buckets for custom (e.g. parallel)
+    /// iteration. This exposes an implementation detail: the layout and
+    /// bucket assignment may change between releases.
+    #[cfg(feature = "raw")]
+    pub fn buckets(&self) -> &[Vec<T>] {
+        &self.buckets
+    }
+
+    /// Entry `i` is the number of buckets holding exactly `i` elements.
+    pub fn bucket_size_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+
+        for bucket in &self.buckets {
+            if bucket.len() >= histogram.len() {
+                histogram.resize(bucket.len() + 1, 0);
+            }
+            histogram[bucket.len()] += 1;
+        }
+
+        histogram
+    }
+
+    /// Order-independent hash of the contents, cached until the next
+    /// mutation. It always uses `DefaultHasher` rather than the set's own
+    /// hasher, so equal sets share a fingerprint whatever their hashers;
+    /// different fingerprints mean the sets are definitely unequal.
+    pub fn fingerprint(&self) -> u64 {
+        *self.fingerprint.get_or_init(|| {
+            self.iter().fold(0u64, |acc, value| {
+                let mut hasher = DefaultHasher::new();
+                value.hash(&mut hasher);
+                acc.wrapping_add(hasher.finish())
+            })
+        })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.buckets)
+    }
+
+    /// Yields the contents of every bucket holding more than one element,
+    /// i.e. the groups of values the hasher (modulo the bucket count) could
+    /// not tell apart.
+    pub fn collision_groups(&self) -> impl Iterator<Item = &[T]> {
+        self.buckets
+            .iter()
+            .filter(|bucket| bucket.len() > 1)
+            .map(Vec::as_slice)
+    }
+
+    /// Splits the bucket indices into `n` contiguous `[start, end)` ranges
+    /// whose sizes differ by at most one, for `iter_range` scans driven from
+    /// separate threads. Fewer than `n` ranges come back when there are
+    /// fewer buckets than that.
+    pub fn bucket_ranges(&self, n: usize) -> Vec<(usize, usize)> {
+        let buckets = self.buckets.len();
+        let n = n.clamp(1, buckets);
+        let (base, extra) = (buckets / n, buckets % n);
+
+        let mut start = 0;
+        (0..n)
+            .map(|i| {
+                let end = start + base + usize::from(i < extra);
+                let range = (start, end);
+                start = end;
+                range
+            })
+            .collect()
+    }
+
+    /// Iterates the elements of buckets `start..end`. Panics if the range
+    /// is out of bounds, like slicing.
+    pub fn iter_range(&self, start: usize, end: usize) -> Iter<'_, T> {
+        Iter::new(&self.buckets[start..end])
+    }
+
+    /// Splits the set into `k` owned sets by contiguous bucket ranges (as
+    /// in `bucket_ranges`), moving every element into exactly one of them,
+    /// for processing the parts independently. Each part gets a clone of
+    /// the hasher; when there are fewer buckets than `k`, the trailing parts
+    /// are empty. Values still staged from `begin_bulk` are flushed first.
+    /// Panics if `k` is zero.
+    pub fn into_partitions(mut self, k: usize) -> Vec<HashSet<T, S>>
+    where
+        S: Clone,
+    {
+        assert!(k > 0, "cannot partition into zero sets");
+        self.end_bulk();
+        let ranges = self.bucket_ranges(k);
+        let mut buckets = self.buckets.into_iter();
+
+        let mut partitions: Vec<HashSet<T, S>> = ranges
+            .iter()
+            .map(|&(start, end)| {
+                let chunk: Vec<Vec<T>> = buckets.by_ref().take(end - start).collect();
+                let len = chunk.iter().map(Vec::len).sum();
+                let mut part = HashSet::with_capacity_and_hasher(len, self.hash_builder.clone());
+                for value in chunk.into_iter().flatten() {
+                    part.insert(value);
+                }
+                part
+            })
+            .collect();
+        partitions.resize_with(k, || HashSet::with_hasher(self.hash_builder.clone()));
+
+        partitions
+    }
+
+    /// Calls `f` on each element, stopping at and returning the first error.
+    pub fn try_for_each<E, F: FnMut(&T) -> Result<(), E>>(&self, f: F) -> Result<(), E> {
+        self.iter().try_for_each(f)
+    }
+
+    /// Yields elements in ascending order. Collects and sorts a temporary
+    /// `Vec<&T>`, so each call is O(n log n).
+    pub fn iter_sorted(&self) -> impl Iterator<Item = &T>
+    where
+        T: Ord,
+    {
+        let mut sorted: Vec<&T> = self.iter().collect();
+        sorted.sort();
+        sorted.into_iter()
+    }
+
+    /// Iterates in an order drawn from `rng`, reproducible for a seeded RNG
+    /// (unlike bucket order, which depends on capacity and hasher).
+    pub fn iter_shuffled<R: Rng + ?Sized>(&self, rng: &mut R) -> impl Iterator<Item = &T> {
+        let mut shuffled: Vec<&T> = self.iter().collect();
+        shuffled.shuffle(rng);
+        shuffled.into_iter()
+    }
+
+    pub fn intersection_count(&self, other: &HashSet<T, S>) -> usize {
+        // Drive the scan from the smaller side so the cost is
+        // O(min(a, b)) regardless of argument order.
+        let (smaller, larger) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        smaller.iter().filter(|v| larger.contains(*v)).count()
+    }
+
+    pub fn intersection_vec(&self, other: &HashSet<T, S>) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let (smaller, larger) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        smaller
+            .iter()
+            .filter(|v| larger.contains(*v))
+            .cloned()
+            .collect()
+    }
+
+    /// Builds a new set holding every element of either set. The result is
+    /// sized for `len() + other.len()` up front so construction never
+    /// resizes, which leaves it oversized when the sets overlap; see
+    /// `union_set_shrunk`.
+    pub fn union_set(&self, other: &HashSet<T, S>) -> HashSet<T, S>
+    where
+        T: Clone,
+        S: Clone,
+    {
+        let mut result =
+            HashSet::with_capacity_and_hasher(self.len() + other.len(), self.hash_builder.clone());
+
+        for value in self.iter().chain(other.iter()) {
+            let hash = result.hash_of(value);
+            if result.find_hashed(hash, |v| v == value).is_none() {
+                result.push_new(hash, value.clone());
+            }
+        }
+
+        result
+    }
+
+    /// Like `union_set`, but trims the result to fit afterwards.
+    pub fn union_set_shrunk(&self, other: &HashSet<T, S>) -> HashSet<T, S>
+    where
+        T: Clone,
+        S: Clone,
+    {
+        let mut result = self.union_set(other);
+        result.shrink_to_fit();
+        result
+    }
+
+    pub fn is_subset(&self, other: &HashSet<T, S>) -> bool {
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v))
+    }
+
+    pub fn is_superset(&self, other: &HashSet<T, S>) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &HashSet<T, S>) -> bool {
+        self.intersection_count(other) == 0
+    }
+
+    pub fn union<'a>(&'a self, other: &'a HashSet<T, S>) -> Union<'a, T, S> {
+        Union {
+            iter: self.iter().chain(other.difference(self)),
+        }
+    }
+
+    pub fn intersection<'a>(&'a self, other: &'a HashSet<T, S>) -> Intersection<'a, T, S> {
+        let (smaller, larger) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        Intersection {
+            iter: smaller.iter(),
+            other: larger,
+        }
+    }
+
+    pub fn difference<'a>(&'a self, other: &'a HashSet<T, S>) -> Difference<'a, T, S> {
+        Difference {
+            iter: self.iter(),
+            other,
+        }
+    }
+
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a HashSet<T, S>,
+    ) -> SymmetricDifference<'a, T, S> {
+        SymmetricDifference {
+            iter: self.difference(other).chain(other.difference(self)),
+        }
+    }
+
+    /// Removes every element also in `other` and returns the removed
+    /// elements, e.g. for an undo log.
+    pub fn difference_collecting(&mut self, other: &HashSet<T, S>) -> Vec<T> {
+        self.drain_filter_reporting(|v| other.contains(v)).0
+    }
+
+    /// Checks that every element appears among the items of `iter`, which
+    /// may be any type the elements borrow as. Since an iterator can only
+    /// be walked once, its items are buffered into a temporary set first
+    /// (sized from the size hint).
+    pub fn is_subset_of_iter<Q, I>(&self, iter: I) -> bool
+    where
+        Q: Hash + Eq,
+        T: Borrow<Q>,
+        I: IntoIterator<Item = Q>,
+    {
+        let iter = iter.into_iter();
+        let mut other: HashSet<Q> = HashSet::with_capacity(iter.size_hint().0);
+        other.extend(iter);
+
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v.borrow()))
+    }
+
+    /// Compares the sets of projected keys rather than the elements, e.g.
+    /// record ids while ignoring payloads. Both key sets are built up front.
+    pub fn eq_by_key<K, F>(&self, other: &HashSet<T, S>, key: F) -> bool
+    where
+        K: Hash + Eq,
+        F: Fn(&T) -> K,
+    {
+        let mine: HashSet<K> = self.iter().map(&key).collect();
+        let theirs: HashSet<K> = other.iter().map(&key).collect();
+
+        mine == theirs
+    }
+
+    /// Checks that the set holds exactly the distinct elements of `iter`;
+    /// duplicates in `iter` collapse.
+    pub fn eq_iter<I: IntoIterator<Item = T>>(&self, iter: I) -> bool {
+        let other: HashSet<T> = iter.into_iter().collect();
+        other.len() == self.len() && other.iter().all(|v| self.contains(v))
+    }
+
+    /// Consumes the set into a sorted boxed slice, a compact read-only
+    /// layout for `binary_search` / `contains_sorted` queries. Values staged
+    /// by a pending bulk load are flushed in first.
+    pub fn into_sorted_boxed(mut self) -> Box<[T]>
+    where
+        T: Ord,
+    {
+        self.end_bulk();
+        let mut values: Vec<T> = self.buckets.into_iter().flatten().collect();
+        values.sort();
+        values.into_boxed_slice()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8>
+    where
+        T: Encode,
+    {
+        let mut out = Vec::new();
+        (self.size as u64).encode(&mut out);
+
+        for value in self.iter() {
+            value.encode(&mut out);
+        }
+
+        out
+    }
+
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut vec = Vec::with_capacity(self.size);
+        vec.extend(self.iter().cloned());
+        vec
+    }
+
+    pub fn diff(&self, previous: &HashSet<T, S>) -> SetDiff<T>
+    where
+        T: Clone,
+    {
+        SetDiff {
+            added: self
+                .iter()
+                .filter(|v| !previous.contains(*v))
+                .cloned()
+                .collect(),
+            removed: previous
+                .iter()
+                .filter(|v| !self.contains(*v))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl<S: BuildHasher> HashSet<u32, S> {
+    /// The members as sorted, maximal runs of consecutive values.
+    pub fn to_ranges(&self) -> Vec<RangeInclusive<u32>> {
+        let mut ranges: Vec<RangeInclusive<u32>> = Vec::new();
+
+        for value in self.iter_sorted().copied() {
+            match ranges.last_mut() {
+                Some(run) if run.end().checked_add(1) == Some(value) => {
+                    *run = *run.start()..=value;
+                }
+                _ => ranges.push(value..=value),
+            }
+        }
+
+        ranges
+    }
+}
+
+impl<S: BuildHasher + Default> HashSet<u32, S> {
+    /// Expands runs back into a set. Overlapping and adjacent runs are
+    /// merged first, so the reservation counts each value once.
+    pub fn from_ranges(ranges: &[RangeInclusive<u32>]) -> Self {
+        let mut sorted: Vec<RangeInclusive<u32>> = ranges
+            .iter()
+            .filter(|run| !run.is_empty())
+            .cloned()
+            .collect();
+        sorted.sort_unstable_by_key(|run| *run.start());
+
+        let mut merged: Vec<RangeInclusive<u32>> = Vec::with_capacity(sorted.len());
+        for run in sorted {
+            match merged.last_mut() {
+                Some(last) if u64::from(*run.start()) <= u64::from(*last.end()) + 1 => {
+                    *last = *last.start()..=(*last.end()).max(*run.end());
+                }
+                _ => merged.push(run),
+            }
+        }
+
+        let total = merged
+            .iter()
+            .map(|run| u64::from(*run.end()) - u64::from(*run.start()) + 1)
+            .sum::<u64>();
+        // A total past `usize` (only possible on 32-bit targets) reserves
+        // nothing up front rather than a truncated count
+        let total = usize::try_from(total).unwrap_or(0);
+        let mut set = Self::with_capacity_and_hasher(total, S::default());
+
+        for run in merged {
+            set.extend(run);
+        }
+
+        set
+    }
+}
+
+#[test]
+fn test_insert_and_contains() {
+    let mut set = HashSet::new();
+    assert!(set.insert(42));
+    assert!(!set.insert(42)); // Duplicate
+    assert!(set.contains(&42));
+    assert!(!set.contains(&99));
+}
+#[test]
+fn test_remove() {
+    let mut set = HashSet::new();
+    set.insert(42);
+    assert!(set.remove(&42));
+    assert!(!set.remove(&42)); // Already removed
+    assert!(!set.contains(&42));
+}
+
+#[test]
+fn test_size_tracking() {
+    let mut set = HashSet::new();
+    assert_eq!(set.len(), 0);
+    assert!(set.is_empty());
+    set.insert(1);
+    set.insert(2);
+    assert_eq!(set.len(), 2);
+    set.remove(&1);
+    assert_eq!(set.len(), 1);
+    set.clear();
+    assert_eq!(set.len(), 0);
+    assert_eq!(set.capacity(), 16); // Capacity unchanged
+}
+
+#[test]
+fn test_resize() {
+    let mut set = HashSet::new();
+    let initial_capacity = set.capacity();
+    // Insert enough to trigger resize (> 0.75 * 16 = 12)
+    for i in 0..20 {
+        set.insert(i);
+    }
+    assert!(set.capacity() > initial_capacity);
+    assert_eq!(set.len(), 20);
+    // Verify all elements still present
+    for i in 0..20 {
+        assert!(set.contains(&i));
+    }
+}
+
+#[test]
+fn test_iterator() {
+    let mut set = HashSet::new();
+    set.insert(1);
     set.insert(2);
     set.insert(3);
     let mut collected: Vec<_> = set.iter().copied().collect();
@@ -221,48 +2029,1587 @@ fn test_iterator() {
     assert_eq!(collected, vec![1, 2, 3]);
 }
 #[test]
-fn test_for_loop() {
-    let mut set = HashSet::new();
-    set.insert("hello");
-    set.insert("world");
-    let mut count = 0;
-    for item in set.iter() {
-        count += 1;
-        assert!(item == &"hello" || item == &"world");
+fn test_for_loop() {
+    let mut set = HashSet::new();
+    set.insert("hello");
+    set.insert("world");
+    let mut count = 0;
+    for item in set.iter() {
+        count += 1;
+        assert!(item == &"hello" || item == &"world");
+    }
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn test_debug() {
+    let mut set = HashSet::new();
+    set.insert(1);
+    set.insert(2);
+    let debug_str = format!("{:?}", set);
+    assert!(debug_str.contains("1"));
+    assert!(debug_str.contains("2"));
+}
+#[test]
+fn test_clone() {
+    let mut set1 = HashSet::new();
+    set1.insert(42);
+    let mut set2 = set1.clone();
+    set2.insert(99);
+    assert!(set1.contains(&42));
+    assert!(!set1.contains(&99)); // Independent copy
+    assert!(set2.contains(&42));
+    assert!(set2.contains(&99));
+}
+#[test]
+fn test_from_iterator() {
+    let set: HashSet<_> = vec![1, 2, 2, 3].into_iter().collect();
+    assert_eq!(set.len(), 3); // Duplicates removed
+    assert!(set.contains(&1));
+    assert!(set.contains(&2));
+    assert!(set.contains(&3));
+}
+#[test]
+fn test_default() {
+    let set: HashSet<i32> = Default::default();
+    assert!(set.is_empty());
+}
+
+#[test]
+fn test_diff() {
+    let current: HashSet<_> = vec![1, 2, 3].into_iter().collect();
+    let previous: HashSet<_> = vec![2, 3, 4].into_iter().collect();
+    let diff = current.diff(&previous);
+    assert_eq!(diff.added, vec![1]);
+    assert_eq!(diff.removed, vec![4]);
+}
+
+#[test]
+fn test_scratch_reuse() {
+    let mut set = HashSet::<u32>::take_scratch();
+    for i in 0..100 {
+        set.insert(i);
+    }
+    let buckets_ptr = set.buckets.as_ptr();
+    let capacity = set.capacity();
+    set.return_scratch();
+
+    let set = HashSet::<u32>::take_scratch();
+    assert!(set.is_empty());
+    assert_eq!(set.capacity(), capacity);
+    assert_eq!(set.buckets.as_ptr(), buckets_ptr); // Same allocation
+}
+
+#[test]
+fn test_scratch_returned_mid_bulk() {
+    let mut set = HashSet::<u64>::take_scratch();
+    set.begin_bulk();
+    set.insert(1);
+    set.insert(2);
+    set.return_scratch();
+
+    let mut set = HashSet::<u64>::take_scratch();
+    assert!(set.is_empty());
+    assert!(set.bulk.is_none());
+    set.end_bulk();
+    assert!(set.is_empty()); // No stale staged values come back
+}
+
+#[test]
+fn test_to_vec() {
+    let set: HashSet<_> = vec![3, 1, 2].into_iter().collect();
+    let mut vec = set.to_vec();
+    vec.sort();
+    assert_eq!(vec, vec![1, 2, 3]);
+    assert_eq!(set.len(), 3); // Original untouched
+    assert!(set.contains(&1));
+}
+
+#[test]
+fn test_matching_indices() {
+    let set: HashSet<_> = vec![20, 70].into_iter().collect();
+    let keys: Vec<i32> = (0..10).map(|i| i * 10).collect();
+    assert_eq!(set.matching_indices(&keys), vec![2, 7]);
+    assert!(set.matching_indices(&[1, 2, 3]).is_empty());
+}
+
+#[test]
+fn test_intersection_count_symmetry() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(8);
+    for _ in 0..200 {
+        let a: Vec<u32> = (0..rng.gen_range(0..60))
+            .map(|_| rng.gen_range(0..100))
+            .collect();
+        let b: Vec<u32> = (0..rng.gen_range(0..60))
+            .map(|_| rng.gen_range(0..100))
+            .collect();
+        let set_a: HashSet<_> = a.iter().copied().collect();
+        let set_b: HashSet<_> = b.iter().copied().collect();
+
+        let mut expected: Vec<u32> = a.iter().copied().filter(|v| b.contains(v)).collect();
+        expected.sort();
+        expected.dedup();
+
+        assert_eq!(set_a.intersection_count(&set_b), expected.len());
+        assert_eq!(set_b.intersection_count(&set_a), expected.len());
+    }
+}
+
+#[test]
+fn test_with_capacity() {
+    let mut set = HashSet::with_capacity(100);
+    let capacity = set.capacity();
+    assert_eq!(capacity, HashSet::<u32>::required_buckets(100));
+    for i in 0..100 {
+        set.insert(i);
+    }
+    assert_eq!(set.capacity(), capacity); // No resize needed
+    assert_eq!(HashSet::<u32>::required_buckets(0), 16);
+}
+
+#[test]
+fn test_insert_profiled() {
+    let mut set = HashSet::new();
+    // 16 buckets hold 12 elements, so the 13th insert resizes
+    let outcomes: Vec<_> = (0..13).map(|i| set.insert_profiled(i)).collect();
+    assert!(outcomes.iter().all(|o| o.inserted));
+    assert_eq!(outcomes.iter().filter(|o| o.resized).count(), 1);
+    assert!(outcomes[12].resized);
+    assert_eq!(set.resize_count(), 1);
+
+    let duplicate = set.insert_profiled(0);
+    assert!(!duplicate.inserted);
+    assert!(!duplicate.resized);
+}
+
+#[test]
+fn test_iter_sorted() {
+    let forward: HashSet<_> = (0..50).collect();
+    let backward: HashSet<_> = (0..50).rev().collect();
+    let expected: Vec<_> = (0..50).collect();
+    assert_eq!(forward.iter_sorted().copied().collect::<Vec<_>>(), expected);
+    assert_eq!(
+        backward.iter_sorted().copied().collect::<Vec<_>>(),
+        expected
+    );
+}
+
+#[test]
+fn test_bucket_stats() {
+    // Lay the buckets out by hand; the stats only look at chain lengths
+    let mut set = HashSet::with_buckets_and_hasher(4, DefaultHashBuilder::default());
+    set.buckets = vec![vec![1, 2, 3], vec![], vec![4], vec![]];
+    set.size = 4;
+    let stats = set.bucket_stats();
+    assert_eq!(stats.longest_chain(), 3);
+    assert_eq!(stats.empty_buckets(), 2);
+    assert_eq!(stats.mean(), 1.0);
+    assert_eq!(stats.variance(), 1.5);
+}
+
+#[test]
+fn test_retain() {
+    let mut set: HashSet<_> = (0..100).collect();
+    set.retain(|v| v % 2 == 0);
+    assert_eq!(set.len(), 50);
+    assert!(set.contains(&10));
+    assert!(!set.contains(&11));
+}
+
+#[test]
+fn test_retain_compacting() {
+    let mut plain: HashSet<u64> = (0..1000).collect();
+    let mut compacted = plain.clone();
+    plain.retain(|v| *v < 5);
+    compacted.retain_compacting(|v| *v < 5);
+    assert_eq!(compacted.len(), 5);
+    assert_eq!(compacted.capacity(), plain.capacity()); // Bucket count unchanged
+    assert!(compacted.memory_usage() < plain.memory_usage());
+    for i in 0..5 {
+        assert!(compacted.contains(&i));
+    }
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct IdentityHasher(u64);
+
+#[cfg(test)]
+impl Hasher for IdentityHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 << 8) | u64::from(*byte);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = n;
+    }
+}
+
+#[test]
+fn test_max_probe_caps_chains() {
+    // Multiples of 64 under an identity hash all share one bucket until the
+    // table outgrows 64 buckets, far before the load factor asks for it
+    let mut set = HashSet::with_hasher(BuildHasherDefault::<IdentityHasher>::default());
+    for k in 0..1000u64 {
+        set.insert(k * 64);
+        assert!(set.bucket_stats().longest_chain() <= set.max_probe());
+    }
+    assert_eq!(set.len(), 1000);
+    assert!((0..1000u64).all(|k| set.contains(&(k * 64))));
+}
+
+#[test]
+fn test_max_probe_bounded_growth() {
+    // Identical hashes can't be split, so growth stops instead of exploding
+    #[derive(PartialEq, Eq)]
+    struct Same(u32);
+    impl Hash for Same {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            0u32.hash(state);
+        }
+    }
+
+    let mut set = HashSet::new();
+    for i in 0..100 {
+        set.insert(Same(i));
+    }
+    assert_eq!(set.len(), 100);
+    assert!(set.capacity() <= set.len() * MAX_PROBE_GROWTH * 2);
+}
+
+#[test]
+fn test_is_subset() {
+    let words: Vec<String> = (0..200).map(|i| format!("word-{i}")).collect();
+    let all: HashSet<String> = words.iter().cloned().collect();
+    let half: HashSet<String> = words.iter().step_by(2).cloned().collect();
+    assert!(half.is_subset(&all));
+    assert!(all.is_superset(&half));
+    assert!(!all.is_subset(&half));
+
+    let mut missing_one = all.clone();
+    missing_one.remove("word-7");
+    let mut outsider = half.clone();
+    outsider.insert("word-7".to_string());
+    outsider.insert("not-a-word".to_string());
+    assert!(!outsider.is_subset(&all));
+    assert!(!all.is_subset(&missing_one));
+
+    // Brute-force reference
+    let reference =
+        |a: &HashSet<String>, b: &HashSet<String>| a.iter().all(|x| b.iter().any(|y| x == y));
+    assert_eq!(half.is_subset(&all), reference(&half, &all));
+    assert_eq!(outsider.is_subset(&all), reference(&outsider, &all));
+}
+
+#[test]
+fn test_is_subset_eq_calls() {
+    thread_local! {
+        static EQ_CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    #[derive(Eq)]
+    struct Counted(String);
+    impl PartialEq for Counted {
+        fn eq(&self, other: &Self) -> bool {
+            EQ_CALLS.with(|c| c.set(c.get() + 1));
+            self.0 == other.0
+        }
+    }
+    impl Hash for Counted {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.0.hash(state);
+        }
+    }
+
+    let words = |step| {
+        (0..2000)
+            .step_by(step)
+            .map(|i| Counted(format!("word-{i}")))
+    };
+    let all: HashSet<Counted> = words(1).collect();
+    let half: HashSet<Counted> = words(2).collect();
+    let mut missing_one: HashSet<Counted> = words(2).collect();
+    missing_one.remove(&Counted("word-1000".to_string()));
+
+    let count = |f: &dyn Fn() -> bool| {
+        EQ_CALLS.with(|c| c.set(0));
+        let result = f();
+        (result, EQ_CALLS.with(|c| c.get()))
+    };
+    let reference =
+        |a: &HashSet<Counted>, b: &HashSet<Counted>| a.iter().all(|x| b.iter().any(|y| x == y));
+
+    for (a, b) in [(&half, &all), (&half, &missing_one)] {
+        let (fast, fast_calls) = count(&|| a.is_subset(b));
+        let (slow, slow_calls) = count(&|| reference(a, b));
+        assert_eq!(fast, slow);
+        // Only the probed bucket's chain is compared, never the whole set
+        assert!(fast_calls <= a.len() * b.bucket_stats().longest_chain());
+        assert!(fast_calls * 10 < slow_calls);
+    }
+}
+
+#[test]
+fn test_reserve() {
+    let mut set = HashSet::new();
+    set.reserve(100);
+    let capacity = set.capacity();
+    assert!(capacity >= HashSet::<u32>::required_buckets(100));
+    for i in 0..100 {
+        set.insert(i);
+    }
+    assert_eq!(set.capacity(), capacity);
+    set.reserve(0); // Never shrinks
+    assert_eq!(set.capacity(), capacity);
+}
+
+#[test]
+fn test_extend_counting() {
+    let mut set: HashSet<_> = vec![1, 2].into_iter().collect();
+    assert_eq!(set.extend_counting([2, 3, 4]), 2);
+    assert_eq!(set.len(), 4);
+    assert_eq!(set.extend_counting([1, 2, 3]), 0);
+}
+
+#[test]
+fn test_reserve_pow2() {
+    let mut set: HashSet<u32> = HashSet::new();
+    set.reserve_pow2(100);
+    assert!(set.capacity().is_power_of_two());
+    assert!(set.capacity() >= HashSet::<u32>::required_buckets(100));
+
+    let mut odd: HashSet<u32> = HashSet::with_capacity(100); // 134 buckets
+    odd.insert(1);
+    odd.reserve_pow2(0);
+    assert_eq!(odd.capacity(), 256);
+    assert!(odd.contains(&1));
+}
+
+#[test]
+fn test_eq_iter() {
+    let set: HashSet<_> = vec![1, 2, 3].into_iter().collect();
+    assert!(set.eq_iter([3, 1, 2, 2]));
+    assert!(!set.eq_iter([1, 2, 3, 4])); // Extra element
+    assert!(!set.eq_iter([1, 2])); // Missing element
+}
+
+#[test]
+fn test_get_or_insert() {
+    let mut set = HashSet::new();
+    assert_eq!(set.get_or_insert("a".to_string()), "a");
+    assert_eq!(set.len(), 1);
+    assert_eq!(set.get_or_insert("a".to_string()), "a"); // Hit
+    assert_eq!(set.len(), 1);
+    for i in 0..100 {
+        assert_eq!(*set.get_or_insert(i.to_string()), i.to_string()); // Across resizes
+    }
+    assert_eq!(set.len(), 101);
+    assert!(set.contains("42"));
+}
+
+#[test]
+fn test_contains_owned() {
+    let set: HashSet<String> = vec!["x".to_string()].into_iter().collect();
+    assert_eq!(set.contains_owned(String::from("x")), set.contains("x"));
+    assert!(!set.contains_owned(String::from("y")));
+}
+
+#[cfg(test)]
+#[derive(Clone)]
+struct ShiftState(u32);
+
+#[cfg(test)]
+impl BuildHasher for ShiftState {
+    type Hasher = ShiftHasher;
+
+    fn build_hasher(&self) -> ShiftHasher {
+        ShiftHasher {
+            shift: self.0,
+            inner: IdentityHasher::default(),
+        }
+    }
+}
+
+// Identity hash shifted right, so larger shifts pile neighbours together
+#[cfg(test)]
+struct ShiftHasher {
+    shift: u32,
+    inner: IdentityHasher,
+}
+
+#[cfg(test)]
+impl Hasher for ShiftHasher {
+    fn finish(&self) -> u64 {
+        self.inner.finish() >> self.shift
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.inner.write(bytes);
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.inner.write_u64(n);
+    }
+}
+
+#[test]
+fn test_rehash_in_place() {
+    let mut set = HashSet::with_hasher(ShiftState(4));
+    for i in 0..100u64 {
+        set.insert(i);
+    }
+    let capacity = set.capacity();
+    let clustered = set.bucket_stats().longest_chain();
+    assert_eq!(clustered, 16);
+
+    set.set_hasher(ShiftState(0));
+    assert!(set.bucket_stats().longest_chain() < clustered);
+    assert_eq!(set.capacity(), capacity);
+    assert_eq!(set.len(), 100);
+    assert!((0..100u64).all(|i| set.contains(&i)));
+}
+
+#[test]
+fn test_drain_filter_reporting() {
+    let mut set: HashSet<_> = (0..10).collect();
+    let capacity = set.capacity();
+    let (mut drained, kept) = set.drain_filter_reporting(|v| v % 2 == 0);
+    drained.sort();
+    assert_eq!(drained, vec![0, 2, 4, 6, 8]);
+    assert_eq!(kept, 5);
+    assert_eq!(set.len(), 5);
+    assert_eq!(set.capacity(), capacity);
+    assert!(set.contains(&1) && !set.contains(&2));
+}
+
+#[test]
+fn test_bulk_insert() {
+    let mut set = HashSet::new();
+    set.insert(0);
+    set.begin_bulk();
+    for i in 0..10_000 {
+        set.insert(i);
+        set.insert(i); // Duplicates collapse at end_bulk
+    }
+    set.end_bulk();
+    assert_eq!(set.resize_count(), 1);
+    assert_eq!(set.len(), 10_000);
+    assert!((0..10_000).all(|i| set.contains(&i)));
+}
+
+#[test]
+fn test_bulk_staged_values_on_clear_and_consume() {
+    let staged = || {
+        let mut set: HashSet<u32> = (0..5).collect();
+        set.begin_bulk();
+        set.insert(10);
+        set.insert(11);
+        set.insert(3); // Already present
+        set
+    };
+
+    let mut cleared = staged();
+    cleared.clear();
+    assert!(cleared.bulk.is_none());
+    cleared.end_bulk();
+    assert!(cleared.is_empty());
+    assert!(cleared.insert(10)); // Inserts are real again
+
+    let mut values: Vec<u32> = staged().into_iter().collect();
+    values.sort();
+    assert_eq!(values, [0, 1, 2, 3, 4, 10, 11]);
+
+    let mut set = staged();
+    let mut drained: Vec<u32> = set.drain().collect();
+    drained.sort();
+    assert_eq!(drained, [0, 1, 2, 3, 4, 10, 11]);
+    assert!(set.is_empty() && set.bulk.is_none());
+
+    assert_eq!(&*staged().into_sorted_boxed(), &[0, 1, 2, 3, 4, 10, 11]);
+}
+
+#[test]
+fn test_bulk_with_long_chains_resizes_once() {
+    let mut set = HashSet::with_hasher(ShiftState(4)); // Chains of 16
+    set.set_max_probe(4);
+
+    set.begin_bulk();
+    assert_eq!(set.insert_batch(0..100u64), 100);
+    assert_eq!(set.insert_batch([0, 1]), 2); // Staged, duplicates included
+    assert!(set.is_empty());
+    set.end_bulk();
+
+    assert_eq!(set.resize_count(), 1);
+    assert_eq!(set.len(), 100);
+    assert_eq!(set.max_probe(), 4);
+    assert!((0..100).all(|i| set.contains(&i)));
+}
+
+#[test]
+fn test_extend_during_bulk_resizes_once() {
+    let mut set: HashSet<u32> = HashSet::new();
+    let before = set.resize_count();
+
+    set.begin_bulk();
+    set.extend(0..10_000);
+    assert_eq!(set.resize_count(), before); // Staged, not reserved for
+    set.end_bulk();
+
+    assert_eq!(set.resize_count(), before + 1);
+    assert_eq!(set.len(), 10_000);
+}
+
+#[test]
+fn test_into_sorted_boxed() {
+    let set: HashSet<_> = vec![42, 7, 19, 3, 100].into_iter().collect();
+    let sorted = set.into_sorted_boxed();
+    assert_eq!(&*sorted, &[3, 7, 19, 42, 100]);
+    for v in [3, 7, 19, 42, 100] {
+        assert!(sorted.binary_search(&v).is_ok());
+        assert!(contains_sorted(&sorted, &v));
+    }
+    assert!(!contains_sorted(&sorted, &8));
+}
+
+#[cfg(feature = "raw")]
+#[test]
+fn test_raw_buckets() {
+    let set: HashSet<_> = (0..100).collect();
+    assert_eq!(set.buckets().len(), set.capacity());
+    let mut flattened: Vec<_> = set.buckets().iter().flatten().copied().collect();
+    let mut iterated: Vec<_> = set.iter().copied().collect();
+    flattened.sort();
+    iterated.sort();
+    assert_eq!(flattened, iterated);
+}
+
+#[test]
+fn test_size_consistency() {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(31);
+    let mut set = HashSet::new();
+    let mut reference = std::collections::HashSet::new();
+    for step in 0..5_000 {
+        let value = rng.gen_range(0..500u32);
+        if rng.gen_bool(0.6) {
+            assert_eq!(set.insert(value), reference.insert(value));
+        } else {
+            assert_eq!(set.remove(&value), reference.remove(&value));
+        }
+        if step % 1_000 == 0 {
+            set.retain(|v| v % 7 != 0);
+            reference.retain(|v| v % 7 != 0);
+        }
+        assert_eq!(set.len(), reference.len());
+        assert_eq!(set.len(), set.buckets.iter().map(Vec::len).sum::<usize>());
+    }
+}
+
+#[test]
+fn test_shrink_to_fit_with_headroom() {
+    let mut set: HashSet<_> = (0..1000).collect();
+    let large = set.capacity();
+    set.retain(|v| *v < 100);
+    set.shrink_to_fit_with_headroom(0.5);
+    assert!(set.capacity() < large);
+    assert_eq!(set.capacity(), HashSet::<i32>::required_buckets(150));
+
+    let resizes = set.resize_count();
+    for i in 100..150 {
+        set.insert(i);
+    }
+    assert_eq!(set.resize_count(), resizes); // Headroom absorbed the growth
+    assert!((0..150).all(|i| set.contains(&i)));
+
+    set.shrink_to_fit();
+    assert_eq!(set.capacity(), HashSet::<i32>::required_buckets(150));
+}
+
+#[test]
+fn test_move_matching_to() {
+    let mut source: HashSet<_> = (0..10).collect();
+    let mut dest: HashSet<_> = vec![4, 100].into_iter().collect();
+    assert_eq!(source.move_matching_to(&mut dest, |v| v % 2 == 0), 5);
+    assert!(source.eq_iter([1, 3, 5, 7, 9]));
+    assert!(dest.eq_iter([0, 2, 4, 6, 8, 100]));
+}
+
+#[test]
+fn test_from_vec() {
+    let unique: HashSet<_> = HashSet::from((0..1000).collect::<Vec<_>>());
+    assert_eq!(unique.len(), 1000);
+    assert_eq!(unique.resize_count(), 0);
+
+    let duplicated: HashSet<_> = HashSet::from((0..1000).map(|i| i % 10).collect::<Vec<_>>());
+    assert_eq!(duplicated.len(), 10);
+    assert_eq!(duplicated.resize_count(), 0);
+    assert_eq!(
+        duplicated.capacity(),
+        HashSet::<i32>::required_buckets(1000)
+    );
+}
+
+#[test]
+fn test_union_sorted() {
+    let set = HashSet::union_sorted([1, 2, 3], [2, 3, 4]);
+    assert!(set.eq_iter([1, 2, 3, 4]));
+    assert_eq!(set.len(), 4);
+
+    let runs = HashSet::union_sorted([1, 1, 1, 5], Vec::new());
+    assert!(runs.eq_iter([1, 5]));
+}
+
+#[test]
+fn test_constant_time_contains() {
+    let secrets: HashSet<Vec<u8>> = (0..50u8).map(|i| vec![i; 16]).collect();
+    assert!(secrets.constant_time_contains(&vec![7; 16]));
+    assert!(!secrets.constant_time_contains(&vec![7; 15]));
+    assert!(!secrets.constant_time_contains(&vec![200; 16]));
+}
+
+#[test]
+fn test_bytes_round_trip() {
+    let set: HashSet<u32> = (0..500).map(|i| i * 7).collect();
+    let bytes = set.to_bytes();
+    assert_eq!(bytes.len(), 8 + 500 * 4);
+    let back = HashSet::<u32>::from_bytes(&bytes).unwrap();
+    assert_eq!(back.len(), 500);
+    assert!(set.iter().all(|v| back.contains(v)));
+
+    let words: HashSet<String> = ["a", "bc", ""].iter().map(|s| s.to_string()).collect();
+    let back = HashSet::<String>::from_bytes(&words.to_bytes()).unwrap();
+    assert!(back.eq_iter(words.to_vec()));
+}
+
+#[test]
+fn test_bytes_malformed() {
+    let bytes = HashSet::<u32>::from_iter([1, 2, 3]).to_bytes();
+    assert_eq!(
+        HashSet::<u32>::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+        DecodeError::UnexpectedEof
+    );
+    assert_eq!(
+        HashSet::<u32>::from_bytes(&[1, 2]).unwrap_err(),
+        DecodeError::UnexpectedEof
+    );
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        HashSet::<u32>::from_bytes(&trailing).unwrap_err(),
+        DecodeError::TrailingBytes(1)
+    );
+    let huge_count = u64::MAX.to_le_bytes();
+    assert!(HashSet::<u8>::from_bytes(&huge_count).is_err()); // No giant allocation
+
+    // Four u32s can't fit in the 12 bytes that follow the count
+    let mut short = 4u64.to_le_bytes().to_vec();
+    short.extend_from_slice(&[0; 12]);
+    assert_eq!(
+        HashSet::<u32>::from_bytes(&short).unwrap_err(),
+        DecodeError::UnexpectedEof
+    );
+
+    // Zero-width elements give no bound, so nothing is reserved for them
+    #[derive(Hash, PartialEq, Eq)]
+    struct Unit;
+    impl Decode for Unit {
+        fn decode(_: &mut &[u8]) -> Result<Self, DecodeError> {
+            Ok(Unit)
+        }
+    }
+    let set = HashSet::<Unit>::from_bytes(&1000u64.to_le_bytes()).unwrap();
+    assert_eq!(set.len(), 1);
+    assert_eq!(set.capacity(), HashSet::<Unit>::required_buckets(1));
+}
+
+#[test]
+fn test_get() {
+    let set: HashSet<String> = vec!["a".to_string()].into_iter().collect();
+    assert_eq!(set.get("a"), Some(&"a".to_string()));
+    assert_eq!(set.get("b"), None);
+}
+
+#[test]
+fn test_pin_lookup_survives_resize() {
+    let mut set: HashSet<String> = vec!["pinned".to_string()].into_iter().collect();
+    let handle = set.pin_lookup("pinned").unwrap();
+    assert!(set.pin_lookup("missing").is_none());
+
+    let capacity = set.capacity();
+    for i in 0..1000 {
+        set.insert(i.to_string());
+    }
+    assert!(set.capacity() > capacity);
+    assert_eq!(handle.resolve(&set).map(String::as_str), Some("pinned"));
+
+    set.remove("pinned");
+    assert_eq!(handle.resolve(&set), None);
+}
+
+#[test]
+fn test_retain_with_policy() {
+    let full: HashSet<_> = (0..1000).collect();
+    let large = full.capacity();
+
+    let mut never = full.clone();
+    never.retain_with_policy(|v| *v < 100, ShrinkPolicy::Never);
+    assert_eq!(never.capacity(), large);
+
+    let mut fit = full.clone();
+    fit.retain_with_policy(|v| *v < 100, ShrinkPolicy::Fit);
+    assert_eq!(fit.capacity(), HashSet::<i32>::required_buckets(100));
+
+    let mut headroom = full;
+    headroom.retain_with_policy(|v| *v < 100, ShrinkPolicy::Headroom(1.0));
+    assert_eq!(headroom.capacity(), HashSet::<i32>::required_buckets(200));
+    assert!((0..100).all(|i| headroom.contains(&i)));
+}
+
+#[test]
+fn test_explain_lookup() {
+    let mut set = HashSet::with_hasher(BuildHasherDefault::<IdentityHasher>::default());
+    for v in [5u64, 21, 37, 2] {
+        set.insert(v); // 5, 21 and 37 share bucket 5 of 16
+    }
+    let explanation = set.explain_lookup(&21);
+    assert_eq!(explanation.hash, 21);
+    assert_eq!(explanation.bucket, 5);
+    assert_eq!(explanation.chain_len, 3);
+    assert_eq!(explanation.position, Some(1));
+
+    let missing = set.explain_lookup(&53);
+    assert_eq!(missing.bucket, 5);
+    assert_eq!(missing.position, None);
+}
+
+#[test]
+fn test_bucket_neighbors() {
+    let mut set = HashSet::with_hasher(BuildHasherDefault::<IdentityHasher>::default());
+    for v in [5u64, 21, 37, 2] {
+        set.insert(v); // 5, 21 and 37 share bucket 5 of 16
+    }
+
+    let mut neighbors = set.bucket_neighbors(&21).unwrap();
+    neighbors.sort();
+    assert_eq!(neighbors, [&5, &37]);
+    assert_eq!(set.bucket_neighbors(&2), Some(vec![]));
+    assert_eq!(set.bucket_neighbors(&53), None); // Same bucket, but absent
+}
+
+#[test]
+fn test_intersection_vec() {
+    thread_local! {
+        static HASHES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    struct Counted(u32);
+    impl Hash for Counted {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            HASHES.with(|h| h.set(h.get() + 1));
+            self.0.hash(state);
+        }
+    }
+
+    let small: HashSet<_> = (0..10).map(Counted).collect();
+    let large: HashSet<_> = (5..1000).map(Counted).collect();
+    for (a, b) in [(&small, &large), (&large, &small)] {
+        HASHES.with(|h| h.set(0));
+        let mut result = a.intersection_vec(b);
+        assert_eq!(HASHES.with(|h| h.get()), small.len()); // One probe per small element
+        result.sort();
+        assert_eq!(result, (5..10).map(Counted).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_reserve_estimated() {
+    let mut set: HashSet<u32> = HashSet::new();
+    set.reserve_estimated(1000, 0.5);
+    assert_eq!(set.capacity(), HashSet::<u32>::required_buckets(500));
+
+    let mut clamped: HashSet<u32> = HashSet::new();
+    clamped.reserve_estimated(1000, 3.0);
+    assert_eq!(clamped.capacity(), HashSet::<u32>::required_buckets(1000));
+}
+
+#[test]
+fn test_fingerprint() {
+    let a: HashSet<_> = (0..100).collect();
+    let mut b: HashSet<_> = (0..100).rev().collect();
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_eq!(a, b);
+
+    let before = b.fingerprint();
+    b.remove(&0);
+    b.insert(100);
+    assert_ne!(b.fingerprint(), before);
+    assert_ne!(a, b); // Same length, rejected by the cached fingerprints
+
+    let mut c = a.clone();
+    c.clear();
+    assert_eq!(c.fingerprint(), HashSet::<i32>::new().fingerprint());
+}
+
+#[test]
+fn test_deferred_removals() {
+    let mut set: HashSet<_> = (0..20).collect();
+    let mut batch = set.defer_removals();
+    for v in set.iter() {
+        if v % 2 == 1 {
+            batch.push(*v);
+        }
+    }
+    batch.push(99); // Not a member
+    assert_eq!(batch.len(), 11);
+    assert_eq!(set.apply_removals(batch), 10);
+    assert!(set.eq_iter((0..20).step_by(2)));
+}
+
+#[test]
+fn test_bucket_size_histogram() {
+    let mut set = HashSet::with_hasher(BuildHasherDefault::<IdentityHasher>::default());
+    for v in [0u64, 16, 32, 1, 17, 2] {
+        set.insert(v); // Chains of 3, 2 and 1 in buckets 0, 1 and 2
+    }
+    let histogram = set.bucket_size_histogram();
+    assert_eq!(histogram, vec![13, 1, 1, 1]);
+    let weighted: usize = histogram.iter().enumerate().map(|(i, n)| i * n).sum();
+    assert_eq!(weighted, set.len());
+    assert_eq!(histogram.iter().sum::<usize>(), set.capacity());
+}
+
+#[test]
+fn test_dedup_iter() {
+    let distinct: Vec<_> = dedup_iter([3, 1, 3, 2, 1, 4]).collect();
+    assert_eq!(distinct, vec![3, 1, 2, 4]);
+
+    // Unbounded input: laziness means `take` is enough to terminate
+    let first: Vec<_> = dedup_iter((0..).map(|i| (i * 3) % 7)).take(7).collect();
+    assert_eq!(first, vec![0, 3, 6, 2, 5, 1, 4]);
+}
+
+#[test]
+fn test_get_or_insert_with_status() {
+    let mut set: HashSet<String> = HashSet::new();
+    let (value, inserted) = set.get_or_insert_with_status("key", str::to_string);
+    assert_eq!((value.as_str(), inserted), ("key", true));
+    let (value, inserted) = set.get_or_insert_with_status("key", |_| unreachable!());
+    assert_eq!((value.as_str(), inserted), ("key", false));
+
+    for i in 0..100 {
+        let key = i.to_string();
+        assert!(
+            set.get_or_insert_with_status(key.as_str(), str::to_string)
+                .1
+        ); // Across resizes
+    }
+    assert_eq!(set.len(), 101);
+}
+
+#[test]
+fn test_union_set_presized() {
+    let a: HashSet<u32> = (0..1000).collect();
+    let b: HashSet<u32> = (500..1500).collect();
+
+    let union = &a | &b;
+    assert_eq!(union.len(), 1500);
+    assert_eq!(union.resize_count(), 0);
+    assert!((0..1500).all(|i| union.contains(&i)));
+
+    // Full overlap leaves the table oversized until shrunk
+    assert_eq!(
+        a.union_set(&a).capacity(),
+        HashSet::<u32>::required_buckets(2000)
+    );
+    let same = a.union_set_shrunk(&a);
+    assert_eq!(same.len(), 1000);
+    assert_eq!(same.capacity(), HashSet::<u32>::required_buckets(1000));
+}
+
+#[test]
+fn test_index_unchecked_present() {
+    let set: HashSet<String> = ["a".to_string()].into_iter().collect();
+    assert_eq!(set.index_unchecked("a"), "a");
+    assert_eq!(set.try_index("a").map(String::as_str), Some("a"));
+    assert_eq!(set.try_index("b"), None);
+}
+
+#[test]
+#[should_panic(expected = "value not present")]
+fn test_index_unchecked_absent() {
+    let set: HashSet<String> = HashSet::new();
+    set.index_unchecked("missing");
+}
+
+#[test]
+fn test_iter_shuffled_reproducible() {
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    let set: HashSet<u32> = (0..200).collect();
+    let first: Vec<u32> = set
+        .iter_shuffled(&mut StdRng::seed_from_u64(7))
+        .copied()
+        .collect();
+    let second: Vec<u32> = set
+        .iter_shuffled(&mut StdRng::seed_from_u64(7))
+        .copied()
+        .collect();
+    assert_eq!(first, second);
+
+    let mut sorted = first.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..200).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_capacity_overflow() {
+    assert_eq!(
+        HashSet::<u32>::try_required_buckets(usize::MAX),
+        Err(TryReserveError)
+    );
+    assert_eq!(
+        HashSet::<u32>::try_required_buckets(usize::MAX / 4),
+        Err(TryReserveError) // Multiplies fine, but too big to allocate
+    );
+    assert_eq!(HashSet::<u32>::try_required_buckets(12), Ok(16));
+
+    let mut set: HashSet<u32> = (0..10).collect();
+    assert_eq!(set.try_reserve(usize::MAX), Err(TryReserveError));
+    assert_eq!(set.try_reserve(usize::MAX - 5), Err(TryReserveError));
+    assert_eq!(set.capacity(), 16); // Untouched on error
+    assert_eq!(set.try_reserve(100), Ok(()));
+    assert!(set.capacity() >= HashSet::<u32>::required_buckets(110));
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn test_with_capacity_overflow_panics() {
+    HashSet::<u32>::with_capacity(usize::MAX);
+}
+
+#[test]
+fn test_from_iter_by_keeps_best() {
+    #[derive(Debug)]
+    struct Versioned {
+        name: &'static str,
+        version: u32,
+    }
+
+    impl PartialEq for Versioned {
+        fn eq(&self, other: &Self) -> bool {
+            self.name == other.name
+        }
+    }
+
+    impl Eq for Versioned {}
+
+    impl Hash for Versioned {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.name.hash(state);
+        }
+    }
+
+    let rows = [("a", 2), ("b", 1), ("a", 5), ("b", 3), ("a", 4)];
+    for reverse in [false, true] {
+        let mut input: Vec<Versioned> = rows
+            .iter()
+            .map(|&(name, version)| Versioned { name, version })
+            .collect();
+        if reverse {
+            input.reverse();
+        }
+
+        let set = HashSet::from_iter_by(input, |existing, incoming| {
+            incoming.version > existing.version
+        });
+        let mut kept: Vec<_> = set.iter().map(|v| (v.name, v.version)).collect();
+        kept.sort();
+        assert_eq!(kept, [("a", 5), ("b", 3)]);
     }
-    assert_eq!(count, 2);
 }
 
 #[test]
-fn test_debug() {
+fn test_bucket_ranges_partition() {
+    let set: HashSet<u32> = (0..500).collect();
+    let ranges = set.bucket_ranges(4);
+    assert_eq!(ranges.len(), 4);
+    assert_eq!(ranges[0].0, 0);
+    assert_eq!(ranges[3].1, set.capacity());
+    assert!(ranges.windows(2).all(|w| w[0].1 == w[1].0));
+
+    let mut seen: Vec<u32> = ranges
+        .iter()
+        .flat_map(|&(start, end)| set.iter_range(start, end))
+        .copied()
+        .collect();
+    seen.sort();
+    assert_eq!(seen, (0..500).collect::<Vec<_>>());
+
+    assert_eq!(HashSet::<u32>::new().bucket_ranges(100).len(), 16);
+}
+
+#[test]
+fn test_insert_with_depth() {
+    let mut set = HashSet::with_hasher(BuildHasherDefault::<IdentityHasher>::default());
+
+    // Multiples of 16 all land in bucket 0 of the initial table
+    for i in 0..8u64 {
+        assert_eq!(set.insert_with_depth(i * 16), (true, i as usize + 1));
+    }
+    assert_eq!(set.insert_with_depth(16), (false, 8));
+    assert_eq!(set.insert_with_depth(1), (true, 1));
+}
+
+#[test]
+fn test_from_vec_reporting() {
+    let (set, duplicates) = HashSet::from_vec_reporting(vec![1, 1, 2, 3, 3, 3]);
+    assert_eq!(set, [1, 2, 3].into_iter().collect());
+    assert_eq!(duplicates, [1, 3, 3]);
+}
+
+#[cfg(feature = "query_cache")]
+#[test]
+fn test_query_cache_invalidation() {
+    let mut set: HashSet<u32> = (0..10).collect();
+    let cached = |set: &HashSet<u32>| set.last_query.pos.load(Ordering::Relaxed) != usize::MAX;
+
+    assert!(!set.contains(&100));
+    assert!(!cached(&set)); // Misses aren't cached
+    assert!(set.contains(&5));
+    assert!(cached(&set));
+    assert!(set.contains(&5)); // Served from the cache
+
+    set.insert(10);
+    assert!(!cached(&set));
+    assert!(set.contains(&5));
+    assert!(set.remove(&5));
+    assert!(!set.contains(&5));
+
+    assert!(set.contains(&6));
+    set.reserve(1000); // Moves elements without changing them
+    assert!(!cached(&set));
+    assert!(set.contains(&6));
+}
+
+#[test]
+fn test_replace_all() {
+    let mut set: HashSet<u32> = (0..10).collect();
+    assert_eq!(set.replace_all(3), [3]);
+    assert!(set.replace_all(30).is_empty());
+    assert_eq!(set.len(), 11);
+
+    // Tag 0 is a wildcard equal to anything; all share one hash
+    #[derive(Debug)]
+    struct Loose(u32);
+
+    impl PartialEq for Loose {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0 || self.0 == 0 || other.0 == 0
+        }
+    }
+
+    impl Eq for Loose {}
+
+    impl Hash for Loose {
+        fn hash<H: Hasher>(&self, _state: &mut H) {}
+    }
+
+    let mut loose: HashSet<Loose> = (1..4).map(Loose).collect();
+    let mut removed: Vec<u32> = loose
+        .replace_all(Loose(0))
+        .into_iter()
+        .map(|v| v.0)
+        .collect();
+    removed.sort();
+    assert_eq!(removed, [1, 2, 3]);
+    assert_eq!(loose.len(), 1);
+}
+
+#[test]
+fn test_retain_transform() {
+    struct Session {
+        id: u32,
+        ttl: u32,
+        renewals: u32,
+    }
+
+    impl PartialEq for Session {
+        fn eq(&self, other: &Self) -> bool {
+            self.id == other.id
+        }
+    }
+
+    impl Eq for Session {}
+
+    impl Hash for Session {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.id.hash(state);
+        }
+    }
+
+    impl Borrow<u32> for Session {
+        fn borrow(&self) -> &u32 {
+            &self.id
+        }
+    }
+
+    let mut set: HashSet<Session> = (0..20)
+        .map(|id| Session {
+            id,
+            ttl: id % 4,
+            renewals: 0,
+        })
+        .collect();
+
+    set.retain_transform(|session| {
+        if session.ttl == 0 {
+            return false;
+        }
+        session.renewals += 1;
+        true
+    });
+
+    assert_eq!(set.len(), 15);
+    for id in 0..20 {
+        assert_eq!(set.contains(&id), id % 4 != 0);
+    }
+    assert!(set.iter().all(|session| session.renewals == 1));
+}
+
+#[test]
+fn test_difference_collecting() {
+    let mut set: HashSet<i32> = [1, 2, 3].into_iter().collect();
+    let other: HashSet<i32> = [2, 3, 4].into_iter().collect();
+
+    let mut removed = set.difference_collecting(&other);
+    removed.sort();
+    assert_eq!(removed, [2, 3]);
+    assert_eq!(set, [1].into_iter().collect());
+}
+
+#[test]
+fn test_is_subset_of_iter() {
+    let set: HashSet<i32> = [1, 2].into_iter().collect();
+    assert!(set.is_subset_of_iter([1, 2, 3]));
+    assert!(!set.is_subset_of_iter([1, 3]));
+    assert!(HashSet::<i32>::new().is_subset_of_iter(std::iter::empty()));
+}
+
+#[test]
+fn test_drain_route() {
+    let mut set: HashSet<i32> = (0..10).collect();
+    let mut even: HashSet<i32> = [0, 100].into_iter().collect();
+    let mut odd = HashSet::new();
+
+    set.drain_route(&mut even, &mut odd, |v| v % 2 == 0);
+    assert!(set.is_empty());
+    assert_eq!(even, [0, 2, 4, 6, 8, 100].into_iter().collect());
+    assert_eq!(odd, [1, 3, 5, 7, 9].into_iter().collect());
+}
+
+#[test]
+fn test_concat_shards_no_rehash() {
+    let capacity = 1000;
+    let mut shards: Vec<HashSet<u32>> = (0..4).map(|_| HashSet::with_capacity(capacity)).collect();
+    let buckets = shards[0].capacity();
+
+    // Partition by bucket index, plus a few duplicates across shards
+    for v in 0..1000u32 {
+        let bucket = (shards[0].hash_of(&v) as usize) % buckets;
+        shards[bucket % 4].insert(v);
+    }
+    shards[1].insert(7);
+    shards[2].insert(7);
+
+    let merged = HashSet::concat_shards(shards);
+    assert_eq!(merged, (0..1000).collect());
+    assert_eq!(merged.resize_count(), 0);
+    assert_eq!(merged.capacity(), buckets);
+
+    // Mismatched bucket counts fall back to re-inserting
+    let small: HashSet<u32> = (0..10).collect();
+    let large: HashSet<u32> = (5..500).collect();
+    let merged = HashSet::concat_shards(vec![small, large]);
+    assert_eq!(merged, (0..500).collect());
+    assert!(HashSet::<u32>::concat_shards(Vec::new()).is_empty());
+}
+
+#[test]
+fn test_concat_shards_flushes_bulk() {
+    let mut first: HashSet<u32> = (0..10).collect();
+    let mut second: HashSet<u32> = (10..20).collect();
+    for shard in [&mut first, &mut second] {
+        shard.begin_bulk();
+    }
+    first.insert(100);
+    second.extend(200..205);
+
+    let merged = HashSet::concat_shards(vec![first, second]);
+    assert_eq!(merged.len(), 26);
+    assert!(merged.contains(&100) && merged.contains(&204));
+}
+
+#[test]
+fn test_collision_groups() {
+    #[derive(Debug, PartialEq, Eq)]
+    struct ConstantHash(u32);
+
+    impl Hash for ConstantHash {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            0u8.hash(state);
+        }
+    }
+
     let mut set = HashSet::new();
-    set.insert(1);
-    set.insert(2);
-    let debug_str = format!("{:?}", set);
-    assert!(debug_str.contains("1"));
-    assert!(debug_str.contains("2"));
+    for i in 0..3 {
+        set.insert(ConstantHash(i));
+    }
+
+    let groups: Vec<&[ConstantHash]> = set.collision_groups().collect();
+    assert_eq!(groups.len(), 1);
+    let mut members: Vec<u32> = groups[0].iter().map(|v| v.0).collect();
+    members.sort();
+    assert_eq!(members, [0, 1, 2]);
+
+    let spread: HashSet<u32> = [1].into_iter().collect();
+    assert_eq!(spread.collision_groups().count(), 0);
 }
+
 #[test]
-fn test_clone() {
-    let mut set1 = HashSet::new();
-    set1.insert(42);
-    let mut set2 = set1.clone();
-    set2.insert(99);
-    assert!(set1.contains(&42));
-    assert!(!set1.contains(&99)); // Independent copy
-    assert!(set2.contains(&42));
-    assert!(set2.contains(&99));
+fn test_seeded_state() {
+    assert_eq!(
+        SeededState::new(1).hash_one(42),
+        SeededState::new(1).hash_one(42)
+    );
+    assert_ne!(
+        SeededState::new(1).hash_one(42),
+        SeededState::new(2).hash_one(42)
+    );
 }
+
 #[test]
-fn test_from_iterator() {
-    let set: HashSet<_> = vec![1, 2, 2, 3].into_iter().collect();
-    assert_eq!(set.len(), 3); // Duplicates removed
-    assert!(set.contains(&1));
-    assert!(set.contains(&2));
-    assert!(set.contains(&3));
+fn test_clone_without_hash_bounds() {
+    #[derive(Clone)]
+    struct Holder<T> {
+        set: HashSet<T>,
+    }
+
+    #[derive(Clone)]
+    struct CloneOnly;
+
+    fn requires_clone<C: Clone>() {}
+
+    // Compiles only if the derived bound stops at `T: Clone`
+    fn holder_is_clone<T: Clone>() {
+        requires_clone::<Holder<T>>();
+    }
+
+    holder_is_clone::<CloneOnly>();
+
+    let holder = Holder {
+        set: [1, 2].into_iter().collect::<HashSet<i32>>(),
+    };
+    assert_eq!(holder.clone().set, holder.set);
 }
+
 #[test]
-fn test_default() {
-    let set: HashSet<i32> = Default::default();
+fn test_insert_batch_single_resize() {
+    let mut set: HashSet<u32> = (0..100).collect();
+    let resizes = set.resize_count();
+
+    // A filter hides the length from the size hint
+    let added = set.insert_batch((0..10_100).filter(|&v| v != u32::MAX));
+    assert_eq!(added, 10_000);
+    assert_eq!(set.len(), 10_100);
+    assert!(set.resize_count() - resizes <= 1);
+}
+
+#[test]
+fn test_remove_all_stops_when_empty() {
+    struct PanicsAfter<'a> {
+        keys: std::slice::Iter<'a, u32>,
+        remaining: usize,
+    }
+
+    impl<'a> Iterator for PanicsAfter<'a> {
+        type Item = &'a u32;
+
+        fn next(&mut self) -> Option<&'a u32> {
+            assert!(self.remaining > 0, "pulled a key after the set was emptied");
+            self.remaining -= 1;
+            self.keys.next()
+        }
+    }
+
+    let mut set: HashSet<u32> = [3, 5, 8].into_iter().collect();
+    let mut keys: Vec<u32> = vec![1, 3, 5, 9, 8];
+    keys.extend(100..10_100);
+
+    let removed = set.remove_all(PanicsAfter {
+        keys: keys.iter(),
+        remaining: 5,
+    });
+    assert_eq!(removed, 3);
     assert!(set.is_empty());
+    assert_eq!(set.remove_all(&keys), 0);
+}
+
+#[test]
+fn test_eq_by_key() {
+    let a: HashSet<(u32, &str)> = [(1, "old"), (2, "old")].into_iter().collect();
+    let b: HashSet<(u32, &str)> = [(1, "new"), (2, "new")].into_iter().collect();
+    let c: HashSet<(u32, &str)> = [(1, "new"), (3, "new")].into_iter().collect();
+
+    assert_ne!(a, b);
+    assert!(a.eq_by_key(&b, |record| record.0));
+    assert!(!a.eq_by_key(&c, |record| record.0));
+}
+
+#[test]
+fn test_contains_bounded() {
+    let mut set = HashSet::with_hasher(BuildHasherDefault::<IdentityHasher>::default());
+    set.set_max_probe(usize::MAX);
+
+    // One chain of 10 in bucket 0, in insertion order
+    for i in 0..10u64 {
+        set.insert(i * 16);
+    }
+
+    assert_eq!(set.contains_bounded(&16, 3), Ok(true));
+    assert_eq!(set.contains_bounded(&144, 3), Err(BudgetExceeded));
+    assert_eq!(set.contains_bounded(&160, 3), Err(BudgetExceeded)); // Absent, undecided
+    assert_eq!(set.contains_bounded(&144, 10), Ok(true));
+    assert_eq!(set.contains_bounded(&160, 10), Ok(false));
+    assert_eq!(set.contains_bounded(&1, 0), Ok(false)); // Empty bucket
+}
+
+#[test]
+fn test_contains_mru() {
+    let mut set = HashSet::with_hasher(BuildHasherDefault::<IdentityHasher>::default());
+    for i in 0..10u64 {
+        set.insert(i * 16);
+    }
+    assert_eq!(set.explain_lookup(&144).position, Some(9));
+
+    assert!(set.contains_mru(&144));
+    assert_eq!(set.explain_lookup(&144).position, Some(0));
+    assert_eq!(set.explain_lookup(&0).position, Some(1)); // Others keep their order
+    assert!(set.contains_mru(&144));
+    assert_eq!(set.explain_lookup(&144).position, Some(0));
+
+    assert!(!set.contains_mru(&1));
+    assert_eq!(set.len(), 10);
+}
+
+#[test]
+fn test_extend_reserves_up_front() {
+    let mut large: HashSet<u32> = HashSet::with_capacity(10_000);
+    large.extend(0..5000);
+    let (capacity, resizes) = (large.capacity(), large.resize_count());
+    large.extend(5000..5010);
+    assert_eq!(large.capacity(), capacity);
+    assert_eq!(large.resize_count(), resizes);
+
+    let mut small: HashSet<u32> = (0..10).collect();
+    let resizes = small.resize_count();
+    small.extend(10..10_000);
+    assert_eq!(small.resize_count(), resizes + 1);
+    assert_eq!(small.len(), 10_000);
+}
+
+#[test]
+fn test_dyn_iterator_bulk_ops() {
+    let mut set = HashSet::new();
+    let mut values: Box<dyn Iterator<Item = u32>> = Box::new((0..10).map(|v| v * 2));
+    set.extend_dyn(&mut values);
+    assert_eq!(set, (0..20).step_by(2).collect());
+
+    let mut keys: Box<dyn Iterator<Item = u32>> = Box::new(0..5);
+    assert_eq!(set.remove_all_dyn(&mut keys), 3);
+    assert_eq!(set.len(), 7);
+}
+
+#[test]
+fn test_probe_by_hash() {
+    let set: HashSet<String> = ["x", "y", "z"].into_iter().map(String::from).collect();
+    let hash = set.raw_hash("x");
+
+    assert!(set.candidates_for_hash(hash).iter().any(|v| v == "x"));
+    assert!(set.contains_any_with_hash(hash));
+    assert!(!set.contains_any_with_hash(set.raw_hash("w")));
+}
+
+#[cfg(test)]
+thread_local! {
+    static PANIC_ON_HASH: std::cell::Cell<Option<u32>> = const { std::cell::Cell::new(None) };
+}
+
+// Panics when hashed while its value is the one in `PANIC_ON_HASH`
+#[cfg(test)]
+#[derive(Debug, PartialEq, Eq)]
+struct FragileHash(u32);
+
+#[cfg(test)]
+impl Hash for FragileHash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if PANIC_ON_HASH.get() == Some(self.0) {
+            panic!("refusing to hash {}", self.0);
+        }
+        self.0.hash(state);
+    }
+}
+
+#[test]
+fn test_panicking_hash_on_insert() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    let mut set: HashSet<FragileHash> = (0..10).map(FragileHash).collect();
+    PANIC_ON_HASH.set(Some(13));
+
+    assert!(catch_unwind(AssertUnwindSafe(|| set.insert(FragileHash(13)))).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| set.remove(&FragileHash(13)))).is_err());
+    PANIC_ON_HASH.set(None);
+
+    assert_eq!(set.len(), 10);
+    assert_eq!(set.iter().count(), 10);
+    assert!(!set.contains(&FragileHash(13)));
+    assert!((0..10).all(|i| set.contains(&FragileHash(i))));
+}
+
+#[test]
+fn test_panicking_hash_during_resize() {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    // 12 elements fill the initial 16 buckets; the next insert resizes
+    let mut set: HashSet<FragileHash> = (0..12).map(FragileHash).collect();
+    let capacity = set.capacity();
+    PANIC_ON_HASH.set(Some(5));
+
+    assert!(catch_unwind(AssertUnwindSafe(|| set.insert(FragileHash(100)))).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| set.rehash_in_place())).is_err());
+    PANIC_ON_HASH.set(None);
+
+    assert_eq!(set.capacity(), capacity);
+    assert_eq!(set.len(), 12);
+    assert_eq!(set.iter().count(), 12);
+    assert!((0..12).all(|i| set.contains(&FragileHash(i))));
+    assert!(set.insert(FragileHash(100)));
+}
+
+#[test]
+fn test_get_batch_sorted_keeps_key_order() {
+    let set: HashSet<u32> = (0..1000).step_by(2).collect();
+    let keys: Vec<u32> = (0..200).rev().collect();
+
+    let results = set.get_batch_sorted(&keys);
+    assert_eq!(results.len(), keys.len());
+    for (key, result) in keys.iter().zip(&results) {
+        assert_eq!(*result, set.get(key));
+    }
+    assert!(set.get_batch_sorted(&[]).is_empty());
+}
+
+#[test]
+fn test_ranges_round_trip() {
+    let set: HashSet<u32> = [1, 2, 3, 7, 8, 100].into_iter().collect();
+    let ranges = set.to_ranges();
+    assert_eq!(ranges, [1..=3, 7..=8, 100..=100]);
+    assert_eq!(HashSet::<u32>::from_ranges(&ranges), set);
+
+    let edges: HashSet<u32> = [0, u32::MAX - 1, u32::MAX].into_iter().collect();
+    assert_eq!(edges.to_ranges(), [0..=0, u32::MAX - 1..=u32::MAX]);
+    assert!(HashSet::<u32>::new().to_ranges().is_empty());
+}
+
+#[test]
+fn test_from_ranges_overlapping() {
+    let set = HashSet::<u32>::from_ranges(&[0..=99, 50..=149, 150..=150, 10..=20]);
+    assert_eq!(set, (0..=150).collect());
+    assert_eq!(set.capacity(), HashSet::<u32>::required_buckets(151));
+
+    // `to_ranges` needs no `Default` hasher
+    let mut seeded = HashSet::with_hasher(SeededState::new(3));
+    seeded.extend(5..10u32);
+    assert_eq!(seeded.to_ranges(), [5..=9]);
+}
+
+#[test]
+fn test_try_for_each_stops_at_error() {
+    let set: HashSet<u32> = (0..100).collect();
+    let mut visited = 0;
+
+    let result = set.try_for_each(|&v| {
+        visited += 1;
+        if v == 42 { Err(v) } else { Ok(()) }
+    });
+    assert_eq!(result, Err(42));
+    assert!(visited < 100 || set.iter().last() == Some(&42));
+
+    assert_eq!(set.try_for_each(|_| Ok::<(), ()>(())), Ok(()));
+}
+
+#[test]
+fn test_replace_contents() {
+    let mut set: HashSet<u32> = (0..100).collect();
+    set.set_max_probe(4);
+    let fingerprint = set.fingerprint();
+
+    let replacement: HashSet<u32> = (1000..1010).collect();
+    set.replace_contents(replacement.clone());
+    assert_eq!(set, replacement);
+    assert_eq!(set.len(), 10);
+    assert!(!set.contains(&0));
+    assert!(set.contains(&1005));
+    assert_eq!(set.max_probe(), 4);
+    assert_ne!(set.fingerprint(), fingerprint);
+}
+
+#[test]
+fn test_into_partitions_disjoint_union() {
+    let set: HashSet<u32> = (0..1000).collect();
+    let partitions = set.clone().into_partitions(4);
+    assert_eq!(partitions.len(), 4);
+    assert_eq!(partitions.iter().map(HashSet::len).sum::<usize>(), 1000);
+
+    for (i, a) in partitions.iter().enumerate() {
+        assert!(!a.is_empty());
+        for b in &partitions[i + 1..] {
+            assert!(a.is_disjoint(b));
+        }
+    }
+
+    let union: HashSet<u32> = partitions.into_iter().flatten().collect();
+    assert_eq!(union, set);
+
+    // More parts than buckets: the extra parts come back empty
+    let small: HashSet<u32> = (0..3).collect();
+    let buckets = small.capacity();
+    let parts = small.into_partitions(buckets + 2);
+    assert_eq!(parts.len(), buckets + 2);
+    assert!(parts[buckets..].iter().all(HashSet::is_empty));
+    assert_eq!(parts.iter().map(HashSet::len).sum::<usize>(), 3);
+}
+
+#[test]
+fn test_into_partitions_flushes_bulk() {
+    let mut set: HashSet<u32> = (0..50).collect();
+    set.begin_bulk();
+    set.extend(50..100);
+
+    let partitions = set.into_partitions(3);
+    let union: HashSet<u32> = partitions.into_iter().flatten().collect();
+    assert_eq!(union, (0..100).collect());
 }
@@ -0,0 +1,133 @@
+use crate::HashSet;
+use crate::tagged::Tagged;
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+/// A set that gives every element a small integer id on first insert. Ids
+/// are handed out sequentially and never reused, even after removal, so they
+/// stay valid as external references (e.g. graph adjacency lists).
+pub struct IdHashSet<T> {
+    entries: HashSet<Tagged<T, u32>>,
+    // Hash of the live element for each issued id, `None` once removed
+    hashes: Vec<Option<u64>>,
+}
+
+impl<T: Hash + Eq> Default for IdHashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> IdHashSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new() -> Self {
+        Self {
+            entries: HashSet::new(),
+            hashes: Vec::new(),
+        }
+    }
+
+    /// Returns whether `value` was newly inserted, along with its id.
+    pub fn insert(&mut self, value: T) -> (bool, u32) {
+        let hash = self.entries.hash_of(&value);
+
+        if let Some(entry) = self.entries.find_hashed(hash, |e| e.value == value) {
+            return (false, entry.tag);
+        }
+
+        let id = u32::try_from(self.hashes.len()).expect("IdHashSet ran out of u32 ids");
+        self.hashes.push(Some(hash));
+        self.entries.push_new(hash, Tagged { tag: id, value });
+
+        (true, id)
+    }
+
+    pub fn id_of<Q>(&self, value: &Q) -> Option<u32>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.entries.hash_of(value);
+
+        self.entries
+            .find_hashed(hash, |e| e.value.borrow() == value)
+            .map(|e| e.tag)
+    }
+
+    pub fn by_id(&self, id: u32) -> Option<&T> {
+        let hash = (*self.hashes.get(id as usize)?)?;
+
+        self.entries
+            .find_hashed(hash, |e| e.tag == id)
+            .map(|e| &e.value)
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.id_of(value).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.entries.hash_of(value);
+
+        match self
+            .entries
+            .take_hashed(hash, |e| e.value.borrow() == value)
+        {
+            Some(entry) => {
+                self.hashes[entry.tag as usize] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &T)> {
+        self.entries.iter().map(|e| (e.tag, &e.value))
+    }
+}
+
+#[test]
+fn test_id_lookups() {
+    let mut set = IdHashSet::new();
+    assert_eq!(set.insert("a"), (true, 0));
+    assert_eq!(set.insert("b"), (true, 1));
+    assert_eq!(set.insert("a"), (false, 0)); // Existing id
+    assert_eq!(set.id_of("b"), Some(1));
+    assert_eq!(set.by_id(0), Some(&"a"));
+    assert_eq!(set.by_id(7), None);
+    assert_eq!(set.id_of("z"), None);
+}
+
+#[test]
+fn test_ids_stable_after_removal() {
+    let mut set = IdHashSet::new();
+    for i in 0..100 {
+        set.insert(i);
+    }
+    assert!(set.remove(&10));
+    assert!(set.remove(&50));
+    assert_eq!(set.by_id(10), None);
+    assert_eq!(set.id_of(&10), None);
+    assert_eq!(set.id_of(&99), Some(99));
+    assert_eq!(set.by_id(11), Some(&11));
+    assert_eq!(set.insert(10), (true, 100)); // Freed ids are not reused
+    assert_eq!(set.len(), 99);
+}
@@ -0,0 +1,79 @@
+use crate::HashSet;
+use std::hash::Hash;
+use std::rc::Rc;
+
+/// A pool of shared values: interning an equal value twice hands back the
+/// same `Rc`, so any number of sets can hold `Rc<T>` while equal values are
+/// stored once.
+pub struct Interner<T> {
+    pool: HashSet<Rc<T>>,
+}
+
+impl<T: Hash + Eq> Default for Interner<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Interner<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new() -> Self {
+        Self {
+            pool: HashSet::new(),
+        }
+    }
+
+    pub fn intern(&mut self, value: T) -> Rc<T> {
+        // Look up by `&T` first so a hit doesn't allocate an `Rc`
+        if let Some(shared) = self.pool.get(&value) {
+            return Rc::clone(shared);
+        }
+
+        Rc::clone(self.pool.get_or_insert(Rc::new(value)))
+    }
+
+    /// The pooled handle for `value`, without interning it.
+    pub fn get(&self, value: &T) -> Option<&Rc<T>> {
+        self.pool.get(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
+
+    /// Drops pooled values no longer referenced outside the interner,
+    /// returning how many were released.
+    pub fn collect_unused(&mut self) -> usize {
+        let before = self.pool.len();
+        self.pool.retain(|shared| Rc::strong_count(shared) > 1);
+
+        before - self.pool.len()
+    }
+}
+
+#[test]
+fn test_interner_shares_allocations() {
+    let mut interner = Interner::new();
+    let a = interner.intern("hello".to_string());
+    let b = interner.intern("hello".to_string());
+    let c = interner.intern("world".to_string());
+
+    assert!(Rc::ptr_eq(&a, &b));
+    assert!(!Rc::ptr_eq(&a, &c));
+    assert_eq!(interner.len(), 2);
+    assert!(Rc::ptr_eq(interner.get(&"hello".to_string()).unwrap(), &a));
+
+    // Sets of handles share the pooled storage
+    let set: HashSet<Rc<String>> = [Rc::clone(&a), c].into_iter().collect();
+    assert!(set.contains(&"hello".to_string()));
+
+    drop(set);
+    assert_eq!(interner.collect_unused(), 1); // Only "world" was unreferenced
+    assert_eq!(interner.len(), 1);
+}
@@ -1,17 +1,32 @@
+use crate::HashSet;
+use std::hash::{BuildHasher, Hash};
+
 pub struct Iter<'a, T> {
     pub(crate) bucket_iter: std::slice::Iter<'a, Vec<T>>,
     pub(crate) current_bucket: Option<std::slice::Iter<'a, T>>,
 }
 
+impl<'a, T> Iter<'a, T> {
+    pub(crate) fn new(buckets: &'a [Vec<T>]) -> Self {
+        let mut bucket_iter = buckets.iter();
+        let current_bucket = bucket_iter.next().map(|b| b.iter());
+
+        Iter {
+            bucket_iter,
+            current_bucket,
+        }
+    }
+}
+
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
 	fn next(&mut self) -> Option<Self::Item> {
         loop {
 
-			if let Some(ref mut bucket) = self.current_bucket {
-				if let Some(item) = bucket.next() {
-					return Some(item);
-				}
+			if let Some(ref mut bucket) = self.current_bucket
+				&& let Some(item) = bucket.next()
+			{
+				return Some(item);
 			}
 
 			match self.bucket_iter.next() {
@@ -24,3 +39,85 @@ impl<'a, T> Iterator for Iter<'a, T> {
 		}
     }
 }
+
+pub struct IntoIter<T> {
+    pub(crate) inner: std::iter::Flatten<std::vec::IntoIter<Vec<T>>>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+}
+
+/// Elements removed by `HashSet::drain`. The set is already empty by the
+/// time this is returned, whether or not it is iterated to the end.
+pub struct Drain<T> {
+    pub(crate) inner: std::vec::IntoIter<T>,
+}
+
+impl<T> Iterator for Drain<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+pub struct Difference<'a, T, S> {
+    pub(crate) iter: Iter<'a, T>,
+    pub(crate) other: &'a HashSet<T, S>,
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for Difference<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let other = self.other;
+        self.iter.find(|v| !other.contains(*v))
+    }
+}
+
+pub struct Intersection<'a, T, S> {
+    pub(crate) iter: Iter<'a, T>,
+    pub(crate) other: &'a HashSet<T, S>,
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for Intersection<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let other = self.other;
+        self.iter.find(|v| other.contains(*v))
+    }
+}
+
+pub struct Union<'a, T, S> {
+    pub(crate) iter: std::iter::Chain<Iter<'a, T>, Difference<'a, T, S>>,
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for Union<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next()
+    }
+}
+
+pub struct SymmetricDifference<'a, T, S> {
+    pub(crate) iter: std::iter::Chain<Difference<'a, T, S>, Difference<'a, T, S>>,
+}
+
+impl<'a, T: Hash + Eq, S: BuildHasher> Iterator for SymmetricDifference<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next()
+    }
+}
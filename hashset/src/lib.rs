@@ -1,5 +1,99 @@
 pub mod hashset;
-pub use hashset::HashSet;
+pub use hashset::{
+    BucketStats, BudgetExceeded, DefaultHashBuilder, HashSet, InsertOutcome, LookupExplanation,
+    PinnedRef, RemovalBatch, SeededState, SetDiff, ShrinkPolicy, TryReserveError, contains_sorted,
+    dedup_iter,
+};
 
 mod iter;
-pub use iter::Iter;
\ No newline at end of file
+pub use iter::{Difference, Drain, Intersection, IntoIter, Iter, SymmetricDifference, Union};
+
+mod dense;
+pub use dense::{DenseHashSet, DenseKey};
+
+mod const_set;
+pub use const_set::ConstHashSet;
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+mod validating;
+pub use validating::ValidatingHashSet;
+
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+
+mod tagged;
+
+mod id_set;
+pub use id_set::IdHashSet;
+
+mod constant_time;
+pub use constant_time::ConstantTimeEq;
+
+pub mod codec;
+pub use codec::{Decode, DecodeError, Encode};
+
+mod category;
+pub use category::CategoryHashSet;
+
+mod append_only;
+pub use append_only::AppendOnlyHashSet;
+
+mod generational;
+pub use generational::GenerationalHashSet;
+
+mod configured;
+pub use configured::ConfiguredHashSet;
+
+mod sharded;
+pub use sharded::ShardedHashSet;
+
+mod background;
+pub use background::BackgroundHashSet;
+
+mod normalizing;
+pub use normalizing::NormalizingHashSet;
+
+mod ring;
+pub use ring::HashRing;
+
+mod cardinality;
+pub use cardinality::CardinalityEstimator;
+
+mod adaptive;
+pub use adaptive::AdaptiveHashSet;
+
+mod interner;
+pub use interner::Interner;
+
+mod flat;
+pub use flat::FlatHashSet;
+
+#[cfg(feature = "unicode_fold")]
+mod unicode_fold;
+#[cfg(feature = "unicode_fold")]
+pub use unicode_fold::UnicodeFoldHashSet;
+
+mod stable;
+pub use stable::{Slot, StableHashSet};
+
+mod slot_set;
+pub use slot_set::SlotHashSet;
+
+mod watermark;
+pub use watermark::WatermarkHashSet;
+
+mod counting_bloom;
+pub use counting_bloom::CountingBloomHashSet;
+
+mod builder;
+pub use builder::HashSetBuilder;
+
+#[cfg(feature = "profiling")]
+mod profiling;
+#[cfg(feature = "profiling")]
+pub use profiling::ProfilingHashSet;
+
+mod reloadable;
+pub use reloadable::ReloadableHashSet;
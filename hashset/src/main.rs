@@ -1,5 +1,3 @@
-use hashset::hashset;
-
 fn main() {
     println!("Hello, world!");
 }
@@ -0,0 +1,71 @@
+use crate::{HashSet, Iter};
+use std::hash::Hash;
+
+type Normalizer<T> = Box<dyn Fn(T) -> T>;
+
+/// A set that stores a canonical form of every value, e.g. lowercased
+/// strings. `normalize` runs before hashing on insert and on every query,
+/// so values that normalize alike collapse into one element.
+pub struct NormalizingHashSet<T> {
+    set: HashSet<T>,
+    normalize: Normalizer<T>,
+}
+
+impl<T> NormalizingHashSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new(normalize: impl Fn(T) -> T + 'static) -> Self {
+        Self {
+            set: HashSet::new(),
+            normalize: Box::new(normalize),
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        self.set.insert((self.normalize)(value))
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.set.contains(&(self.normalize)(value))
+    }
+
+    pub fn remove(&mut self, value: T) -> bool {
+        let value = (self.normalize)(value);
+        self.set.remove(&value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Yields the stored, normalized forms.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.set.iter()
+    }
+
+    pub fn into_inner(self) -> HashSet<T> {
+        self.set
+    }
+}
+
+#[test]
+fn test_normalizing_case_insensitive() {
+    let mut set = NormalizingHashSet::new(|s: String| s.to_lowercase());
+    assert!(set.insert("Foo".to_string()));
+    assert!(!set.insert("foo".to_string()));
+    assert!(!set.insert("FOO".to_string()));
+    assert!(set.insert("Bar".to_string()));
+    assert_eq!(set.len(), 2);
+
+    assert!(set.contains("fOo".to_string()));
+    assert!(!set.contains("baz".to_string()));
+    assert!(set.iter().all(|s| *s == s.to_lowercase()));
+
+    assert!(set.remove("BAR".to_string()));
+    assert_eq!(set.len(), 1);
+}
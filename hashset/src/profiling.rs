@@ -0,0 +1,160 @@
+use crate::HashSet;
+use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Hashes and compares by `value` alone, so the hit counter can change
+// without moving the entry.
+struct Entry<T> {
+    value: T,
+    hits: AtomicU64,
+}
+
+impl<T: Hash> Hash for Entry<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T: PartialEq> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Entry<T> {}
+
+/// A set that counts how often each element is found by `contains` or
+/// `get`, for spotting hot keys when sizing a cache. Counters are atomics
+/// bumped through `&self`, so lookups keep their usual signature and the
+/// set stays `Sync`; misses are not recorded.
+pub struct ProfilingHashSet<T> {
+    set: HashSet<Entry<T>>,
+}
+
+impl<T: Hash + Eq> Default for ProfilingHashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ProfilingHashSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new() -> Self {
+        Self {
+            set: HashSet::new(),
+        }
+    }
+
+    /// Inserts `value` with a hit count of zero. Re-inserting an existing
+    /// element keeps its count.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.set.insert(Entry {
+            value,
+            hits: AtomicU64::new(0),
+        })
+    }
+
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.set.hash_of(value);
+        let entry = self.set.find_hashed(hash, |e| e.value.borrow() == value)?;
+        entry.hits.fetch_add(1, Ordering::Relaxed);
+
+        Some(&entry.value)
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.get(value).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.set.hash_of(value);
+        self.set
+            .take_hashed(hash, |e| e.value.borrow() == value)
+            .is_some()
+    }
+
+    /// Hits recorded for `value` so far, without counting this query.
+    pub fn hits<Q>(&self, value: &Q) -> Option<u64>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.set.hash_of(value);
+        self.set
+            .find_hashed(hash, |e| e.value.borrow() == value)
+            .map(|e| e.hits.load(Ordering::Relaxed))
+    }
+
+    /// The `n` most-queried elements with their hit counts, hottest first.
+    /// Ties come out in arbitrary order.
+    pub fn top_accessed(&self, n: usize) -> Vec<(&T, u64)> {
+        let mut counts: Vec<(&T, u64)> = self
+            .set
+            .iter()
+            .map(|e| (&e.value, e.hits.load(Ordering::Relaxed)))
+            .collect();
+        counts.sort_unstable_by_key(|&(_, hits)| Reverse(hits));
+        counts.truncate(n);
+        counts
+    }
+
+    /// Zeroes every counter, e.g. between measurement windows.
+    pub fn reset_hits(&mut self) {
+        for entry in self.set.iter() {
+            entry.hits.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.set.iter().map(|e| &e.value)
+    }
+}
+
+#[test]
+fn test_profiling_top_accessed() {
+    let mut set = ProfilingHashSet::new();
+    for value in ["cold", "warm", "hot", "unused"] {
+        set.insert(value);
+    }
+
+    for _ in 0..10 {
+        assert!(set.contains("hot"));
+    }
+    for _ in 0..3 {
+        assert_eq!(set.get("warm"), Some(&"warm"));
+    }
+    set.contains("cold");
+    assert!(!set.contains("missing"));
+
+    assert_eq!(set.top_accessed(1), vec![(&"hot", 10)]);
+    assert_eq!(set.top_accessed(2)[1], (&"warm", 3));
+    assert_eq!(set.top_accessed(10).len(), 4);
+    assert_eq!(set.hits("unused"), Some(0));
+
+    set.reset_hits();
+    assert_eq!(set.hits("hot"), Some(0));
+}
@@ -0,0 +1,75 @@
+use crate::HashSet;
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+
+/// A shared set for the config-reload pattern: readers take a cheap
+/// `Arc` snapshot with `load`, and a writer publishes a fully built
+/// replacement with `reload`. The lock is only held to clone or swap the
+/// `Arc`, so a reload never waits on readers still holding the old
+/// snapshot, and they keep seeing a consistent set until they drop it.
+///
+/// For a set owned outright, `HashSet::replace_contents` does the same
+/// swap in place.
+pub struct ReloadableHashSet<T> {
+    current: RwLock<Arc<HashSet<T>>>,
+}
+
+impl<T: Hash + Eq> Default for ReloadableHashSet<T> {
+    fn default() -> Self {
+        Self::new(HashSet::new())
+    }
+}
+
+impl<T> ReloadableHashSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new(set: HashSet<T>) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(set)),
+        }
+    }
+
+    /// The live set as of now; later reloads don't affect it.
+    pub fn load(&self) -> Arc<HashSet<T>> {
+        Arc::clone(&self.current.read().unwrap())
+    }
+
+    /// Publishes `new` and returns the set it replaced. The old contents
+    /// are dropped once the last outstanding snapshot goes away.
+    pub fn reload(&self, new: HashSet<T>) -> Arc<HashSet<T>> {
+        std::mem::replace(&mut *self.current.write().unwrap(), Arc::new(new))
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.load().contains(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.load().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.load().is_empty()
+    }
+}
+
+#[test]
+fn test_reload_keeps_old_snapshots() {
+    let live = ReloadableHashSet::new(["a", "b"].into_iter().collect());
+    let before = live.load();
+
+    let old = live.reload(["c"].into_iter().collect());
+    assert!(live.contains("c"));
+    assert!(!live.contains("a"));
+    assert_eq!(live.len(), 1);
+
+    // Readers holding the old snapshot still see it intact
+    assert!(before.contains("a") && before.contains("b"));
+    assert!(Arc::ptr_eq(&before, &old));
+}
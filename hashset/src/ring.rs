@@ -0,0 +1,120 @@
+use crate::DefaultHashBuilder;
+use std::hash::{BuildHasher, Hash};
+
+const DEFAULT_REPLICAS: usize = 100;
+
+/// A consistent-hashing ring. Each node is placed at `replicas` points on
+/// a ring of hash values, and a key belongs to the first node point at or
+/// after the key's own hash (wrapping around). Adding or removing a node
+/// only moves the keys that land next to its points.
+pub struct HashRing<Node> {
+    nodes: Vec<Node>,
+    // Sorted by hash; the `usize` indexes `nodes`
+    points: Vec<(u64, usize)>,
+    replicas: usize,
+    hash_builder: DefaultHashBuilder,
+}
+
+impl<Node: Hash + Eq> Default for HashRing<Node> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Node> HashRing<Node>
+where
+    Node: Hash + Eq,
+{
+    pub fn new() -> Self {
+        Self::with_replicas(DEFAULT_REPLICAS)
+    }
+
+    /// More replicas spread each node's share more evenly, at the cost of
+    /// a larger ring.
+    pub fn with_replicas(replicas: usize) -> Self {
+        Self {
+            nodes: Vec::new(),
+            points: Vec::new(),
+            replicas: replicas.max(1),
+            hash_builder: DefaultHashBuilder::default(),
+        }
+    }
+
+    /// Adds `node`, returning `false` if it was already on the ring.
+    pub fn add_node(&mut self, node: Node) -> bool {
+        if self.nodes.contains(&node) {
+            return false;
+        }
+
+        let index = self.nodes.len();
+        for replica in 0..self.replicas {
+            let hash = self.hash_builder.hash_one((&node, replica));
+            self.points.push((hash, index));
+        }
+        self.points.sort_unstable();
+        self.nodes.push(node);
+
+        true
+    }
+
+    pub fn remove_node(&mut self, node: &Node) -> bool {
+        let Some(index) = self.nodes.iter().position(|n| n == node) else {
+            return false;
+        };
+
+        self.nodes.swap_remove(index);
+        self.points.retain(|&(_, i)| i != index);
+
+        // The last node moved into the freed slot
+        let moved = self.nodes.len();
+        for point in &mut self.points {
+            if point.1 == moved {
+                point.1 = index;
+            }
+        }
+
+        true
+    }
+
+    pub fn node_for<K: Hash + ?Sized>(&self, key: &K) -> Option<&Node> {
+        let hash = self.hash_builder.hash_one(key);
+        let next = self.points.partition_point(|&(point, _)| point < hash);
+        let (_, index) = self.points.get(next).or_else(|| self.points.first())?;
+
+        Some(&self.nodes[*index])
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+#[test]
+fn test_ring_removal_only_remaps_removed_node() {
+    let mut ring = HashRing::new();
+    assert_eq!(ring.node_for(&1), None);
+    for node in ["a", "b", "c"] {
+        assert!(ring.add_node(node));
+    }
+    assert!(!ring.add_node("b"));
+
+    let before: Vec<&str> = (0..3000).map(|key| *ring.node_for(&key).unwrap()).collect();
+    for node in ["a", "b", "c"] {
+        assert!(before.contains(&node));
+    }
+
+    assert!(ring.remove_node(&"a"));
+    assert!(!ring.remove_node(&"a"));
+    for (key, owner) in (0..3000).zip(&before) {
+        let now = *ring.node_for(&key).unwrap();
+        if *owner == "a" {
+            assert_ne!(now, "a");
+        } else {
+            assert_eq!(now, *owner);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::HashSet;
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+impl<T: Serialize + Hash + Eq, H: BuildHasher> Serialize for HashSet<T, H> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Elements are deserialized with the input's `'de` lifetime, so borrowed
+/// element types such as `HashSet<&'de str>` work zero-copy. The borrow only
+/// succeeds when the format can hand out a slice of the input: for JSON that
+/// means strings without escape sequences, and the input buffer must outlive
+/// the set.
+impl<'de, T, H> Deserialize<'de> for HashSet<T, H>
+where
+    T: Deserialize<'de> + Hash + Eq,
+    H: BuildHasher + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SetVisitor<T, H>(PhantomData<(T, H)>);
+
+        impl<'de, T, H> Visitor<'de> for SetVisitor<T, H>
+        where
+            T: Deserialize<'de> + Hash + Eq,
+            H: BuildHasher + Default,
+        {
+            type Value = HashSet<T, H>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut set = HashSet::default();
+
+                while let Some(value) = seq.next_element()? {
+                    set.insert(value);
+                }
+
+                Ok(set)
+            }
+        }
+
+        deserializer.deserialize_seq(SetVisitor(PhantomData))
+    }
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let set: HashSet<_> = vec![1, 2, 3].into_iter().collect();
+    let json = serde_json::to_string(&set).unwrap();
+    let back: HashSet<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.len(), 3);
+    assert!(back.contains(&1) && back.contains(&2) && back.contains(&3));
+}
+
+#[test]
+fn test_serde_borrowed_str() {
+    let json: &'static str = r#"["alpha", "beta", "alpha"]"#;
+    let set: HashSet<&str> = serde_json::from_str(json).unwrap();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains("alpha"));
+    assert!(set.contains("beta"));
+
+    let input = json.as_bytes().as_ptr_range();
+    assert!(set.iter().all(|s| input.contains(&s.as_ptr()))); // Borrowed, not copied
+}
@@ -0,0 +1,107 @@
+use crate::{HashSet, Iter};
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+/// A set whose elements are assigned to one of a fixed number of shards by
+/// the high bits of their hash, for exporting to shard files that must match
+/// a downstream partitioning. Shard assignment ignores the bucket index, so
+/// it stays stable as the table grows.
+pub struct ShardedHashSet<T> {
+    set: HashSet<T>,
+    shards: usize,
+}
+
+impl<T> ShardedHashSet<T>
+where
+    T: Hash + Eq,
+{
+    /// Panics if `shards` is zero.
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "ShardedHashSet needs at least one shard");
+
+        Self {
+            set: HashSet::new(),
+            shards,
+        }
+    }
+
+    pub fn shards(&self) -> usize {
+        self.shards
+    }
+
+    pub fn shard_of<Q>(&self, value: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.shard_of_hash(self.set.hash_of(value))
+    }
+
+    // Multiply-shift maps the hash onto `0..shards` using its high bits,
+    // where bucket indexing (`hash % buckets`) uses the low ones.
+    fn shard_of_hash(&self, hash: u64) -> usize {
+        ((u128::from(hash) * self.shards as u128) >> 64) as usize
+    }
+
+    /// Yields the elements of `shard`, scanning the whole set.
+    pub fn iter_shard(&self, shard: usize) -> impl Iterator<Item = &T> {
+        self.set
+            .iter()
+            .filter(move |v| self.shard_of_hash(self.set.hash_of(*v)) == shard)
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        self.set.insert(value)
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.contains(value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.remove(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.set.iter()
+    }
+
+    pub fn into_inner(self) -> HashSet<T> {
+        self.set
+    }
+}
+
+#[test]
+fn test_sharded_partition() {
+    let mut set = ShardedHashSet::with_shards(4);
+    for i in 0..1000 {
+        set.insert(i);
+    }
+
+    let mut seen = Vec::new();
+    for shard in 0..4 {
+        let members: Vec<i32> = set.iter_shard(shard).copied().collect();
+        assert!(!members.is_empty());
+        assert!(members.iter().all(|v| set.shard_of(v) == shard));
+        seen.extend(members);
+    }
+    seen.sort();
+    assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+    assert_eq!(set.iter_shard(4).count(), 0);
+}
@@ -0,0 +1,141 @@
+use crate::HashSet;
+use crate::tagged::Tagged;
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+/// A set that gives every live element a slot number for indexing parallel
+/// arrays (e.g. ECS component storage). Unlike `IdHashSet` ids, slots of
+/// removed elements are reused, so slot numbers stay dense: they never
+/// exceed the peak number of elements held at once.
+pub struct SlotHashSet<T> {
+    entries: HashSet<Tagged<T, usize>>,
+    // Hash of the element in each slot, `None` while the slot is free
+    hashes: Vec<Option<u64>>,
+    free: Vec<usize>,
+}
+
+impl<T: Hash + Eq> Default for SlotHashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SlotHashSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new() -> Self {
+        Self {
+            entries: HashSet::new(),
+            hashes: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Returns whether `value` was newly inserted, along with its slot.
+    pub fn insert(&mut self, value: T) -> (bool, usize) {
+        let hash = self.entries.hash_of(&value);
+
+        if let Some(entry) = self.entries.find_hashed(hash, |e| e.value == value) {
+            return (false, entry.tag);
+        }
+
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.hashes[slot] = Some(hash);
+                slot
+            }
+            None => {
+                self.hashes.push(Some(hash));
+                self.hashes.len() - 1
+            }
+        };
+        self.entries.push_new(hash, Tagged { tag: slot, value });
+
+        (true, slot)
+    }
+
+    pub fn slot_of<Q>(&self, value: &Q) -> Option<usize>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.entries.hash_of(value);
+
+        self.entries
+            .find_hashed(hash, |e| e.value.borrow() == value)
+            .map(|e| e.tag)
+    }
+
+    pub fn by_slot(&self, slot: usize) -> Option<&T> {
+        let hash = (*self.hashes.get(slot)?)?;
+
+        self.entries
+            .find_hashed(hash, |e| e.tag == slot)
+            .map(|e| &e.value)
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.slot_of(value).is_some()
+    }
+
+    /// Removes `value`, returning the slot it freed.
+    pub fn remove<Q>(&mut self, value: &Q) -> Option<usize>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let hash = self.entries.hash_of(value);
+        let entry = self
+            .entries
+            .take_hashed(hash, |e| e.value.borrow() == value)?;
+
+        self.hashes[entry.tag] = None;
+        self.free.push(entry.tag);
+
+        Some(entry.tag)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// One past the highest slot ever handed out; a parallel array this
+    /// long can be indexed by any slot.
+    pub fn slot_capacity(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.entries.iter().map(|e| (e.tag, &e.value))
+    }
+}
+
+#[test]
+fn test_slots_reused_after_removal() {
+    let mut set = SlotHashSet::new();
+    assert_eq!(set.insert("a"), (true, 0));
+    assert_eq!(set.insert("b"), (true, 1));
+    assert_eq!(set.insert("c"), (true, 2));
+    assert_eq!(set.insert("b"), (false, 1));
+
+    assert_eq!(set.remove("b"), Some(1));
+    assert_eq!(set.remove("b"), None);
+    assert_eq!(set.by_slot(1), None);
+    assert_eq!(set.slot_of("b"), None);
+
+    assert_eq!(set.insert("d"), (true, 1)); // Freed slot comes back
+    assert_eq!(set.by_slot(1), Some(&"d"));
+    assert_eq!(set.slot_of("c"), Some(2));
+    assert_eq!(set.insert("e"), (true, 3));
+    assert_eq!(set.slot_capacity(), 4);
+    assert_eq!(set.len(), 4);
+}
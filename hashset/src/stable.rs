@@ -0,0 +1,276 @@
+use crate::{DefaultHashBuilder, HashSet};
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+
+/// A position in a `StableHashSet`, valid until the next `compact` or
+/// growth of the set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    bucket: usize,
+    pos: usize,
+    epoch: u64,
+    generation: u64,
+}
+
+// A tombstone keeps its generation, and reusing it bumps the count, so a
+// `Slot` taken before the removal can't resolve to the newcomer.
+struct Entry<T> {
+    value: Option<T>,
+    generation: u64,
+}
+
+/// A set whose removals leave tombstones instead of shifting neighbours,
+/// so a `Slot` obtained from `locate` keeps pointing at the same element
+/// while others come and go. Inserts reuse tombstones in their bucket.
+/// Only `compact` and growth move elements; they start a new epoch, after
+/// which older slots resolve to `None` rather than to the wrong element.
+pub struct StableHashSet<T> {
+    buckets: Vec<Vec<Entry<T>>>,
+    size: usize,
+    tombstones: usize,
+    epoch: u64,
+    hash_builder: DefaultHashBuilder,
+}
+
+impl<T: Hash + Eq> Default for StableHashSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> StableHashSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buckets: create_buckets(HashSet::<T>::required_buckets(capacity)),
+            size: 0,
+            tombstones: 0,
+            epoch: 0,
+            hash_builder: DefaultHashBuilder::default(),
+        }
+    }
+
+    fn bucket_index<Q: Hash + ?Sized>(&self, value: &Q) -> usize {
+        (self.hash_builder.hash_one(value) as usize) % self.buckets.len()
+    }
+
+    fn position<Q>(&self, bucket: usize, value: &Q) -> Option<usize>
+    where
+        Q: Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.buckets[bucket]
+            .iter()
+            .position(|entry| entry.value.as_ref().is_some_and(|v| v.borrow() == value))
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+
+        if (self.size + 1) * 4 > self.buckets.len() * 3 {
+            let target = self.buckets.len().checked_mul(2);
+            self.rebuild(target.expect("HashSet capacity overflow"));
+        }
+
+        let index = self.bucket_index(&value);
+        let bucket = &mut self.buckets[index];
+
+        match bucket.iter_mut().find(|entry| entry.value.is_none()) {
+            Some(tombstone) => {
+                tombstone.value = Some(value);
+                tombstone.generation += 1;
+                self.tombstones -= 1;
+            }
+            None => bucket.push(Entry {
+                value: Some(value),
+                generation: 0,
+            }),
+        }
+        self.size += 1;
+
+        true
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.locate(value).is_some()
+    }
+
+    pub fn locate<Q>(&self, value: &Q) -> Option<Slot>
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let bucket = self.bucket_index(value);
+        let pos = self.position(bucket, value)?;
+
+        Some(Slot {
+            bucket,
+            pos,
+            epoch: self.epoch,
+            generation: self.buckets[bucket][pos].generation,
+        })
+    }
+
+    /// The element at `slot`, or `None` if it was removed or the slot is
+    /// from before a `compact` or growth.
+    pub fn get_at(&self, slot: Slot) -> Option<&T> {
+        if slot.epoch != self.epoch {
+            return None;
+        }
+
+        let entry = self.buckets.get(slot.bucket)?.get(slot.pos)?;
+        if entry.generation != slot.generation {
+            return None;
+        }
+
+        entry.value.as_ref()
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        let bucket = self.bucket_index(value);
+        let Some(pos) = self.position(bucket, value) else {
+            return false;
+        };
+
+        self.buckets[bucket][pos].value = None;
+        self.size -= 1;
+        self.tombstones += 1;
+
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn tombstones(&self) -> usize {
+        self.tombstones
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buckets
+            .iter()
+            .flatten()
+            .filter_map(|entry| entry.value.as_ref())
+    }
+
+    /// Drops every tombstone, invalidating all outstanding slots.
+    pub fn compact(&mut self) {
+        for bucket in &mut self.buckets {
+            bucket.retain(|entry| entry.value.is_some());
+        }
+
+        self.tombstones = 0;
+        self.epoch += 1;
+    }
+
+    fn rebuild(&mut self, bucket_count: usize) {
+        let mut buckets = create_buckets(bucket_count);
+
+        for value in self
+            .buckets
+            .drain(..)
+            .flatten()
+            .filter_map(|entry| entry.value)
+        {
+            let index = (self.hash_builder.hash_one(&value) as usize) % bucket_count;
+            buckets[index].push(Entry {
+                value: Some(value),
+                generation: 0,
+            });
+        }
+
+        self.buckets = buckets;
+        self.tombstones = 0;
+        self.epoch += 1;
+    }
+}
+
+fn create_buckets<T>(size: usize) -> Vec<Vec<Entry<T>>> {
+    std::iter::repeat_with(Vec::new).take(size).collect()
+}
+
+#[test]
+fn test_stable_slots_survive_removals() {
+    let mut set = StableHashSet::with_capacity(100);
+    for i in 0..50 {
+        set.insert(i);
+    }
+
+    let held = set.locate(&25).unwrap();
+    for i in (0..50).filter(|i| *i != 25) {
+        assert!(set.remove(&i));
+        assert_eq!(set.get_at(held), Some(&25));
+    }
+    assert_eq!(set.len(), 1);
+    assert_eq!(set.tombstones(), 49);
+
+    // Tombstones are reused without moving the held element
+    for i in 100..140 {
+        set.insert(i);
+    }
+    assert_eq!(set.get_at(held), Some(&25));
+    assert!(set.tombstones() < 49);
+
+    set.compact();
+    assert_eq!(set.tombstones(), 0);
+    assert_eq!(set.get_at(held), None); // Stale after compaction
+    assert_eq!(set.get_at(set.locate(&25).unwrap()), Some(&25));
+}
+
+#[test]
+fn test_stable_removed_slot_and_growth() {
+    let mut set = StableHashSet::new();
+    set.insert(0);
+    let slot = set.locate(&0).unwrap();
+    assert!(set.remove(&0));
+    assert_eq!(set.get_at(slot), None);
+    assert!(!set.remove(&0));
+
+    set.insert(1);
+    let slot = set.locate(&1).unwrap();
+    for i in 2..100 {
+        set.insert(i);
+    }
+    assert_eq!(set.get_at(slot), None); // Growth moved everything
+    assert_eq!(set.len(), 99);
+    assert_eq!(set.iter().count(), 99);
+    assert!((1..100).all(|i| set.contains(&i)));
+}
+
+#[test]
+fn test_stable_reused_tombstone_invalidates_slot() {
+    let mut set = StableHashSet::with_capacity(100);
+    set.insert(0u32);
+    let slot = set.locate(&0).unwrap();
+    assert!(set.remove(&0));
+
+    // Find another value landing in the same bucket, reusing the tombstone
+    let other = (1..).find(|v| set.bucket_index(v) == slot.bucket).unwrap();
+    set.insert(other);
+    assert_eq!(set.tombstones(), 0);
+    assert_eq!(set.locate(&other).unwrap().pos, slot.pos);
+
+    assert_eq!(set.get_at(slot), None);
+    assert_eq!(set.get_at(set.locate(&other).unwrap()), Some(&other));
+}
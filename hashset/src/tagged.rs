@@ -0,0 +1,23 @@
+use std::hash::{Hash, Hasher};
+
+// An element paired with bookkeeping such as an id or slot number. Hashes
+// and compares by value only, so it lands in the same bucket a bare `T`
+// (or any `Q` it borrows as) would.
+pub(crate) struct Tagged<T, Tag> {
+    pub(crate) tag: Tag,
+    pub(crate) value: T,
+}
+
+impl<T: Hash, Tag> Hash for Tagged<T, Tag> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T: PartialEq, Tag> PartialEq for Tagged<T, Tag> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, Tag> Eq for Tagged<T, Tag> {}
@@ -0,0 +1,283 @@
+use crate::HashSet;
+use std::hash::{Hash, Hasher};
+
+// Stores the first-inserted spelling; hashes and compares by the fold only.
+struct Entry {
+    folded: String,
+    original: String,
+}
+
+impl Hash for Entry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.folded.hash(state);
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.folded == other.folded
+    }
+}
+
+impl Eq for Entry {}
+
+/// A set of strings that treats case variants as equal, keeping the
+/// spelling first inserted.
+///
+/// Every character goes through `char::to_lowercase` and then a table of
+/// the characters where Unicode full case folding differs from lowercasing
+/// (`ß`/`ẞ` to `ss`, final sigma, the Greek iota subscripts, the Latin and
+/// Armenian ligatures and so on), so "STRASSE", "strasse" and "straße"
+/// collapse. The folding is the default, non-Turkic one: `I` folds to `i`,
+/// `İ` to `i` followed by U+0307 COMBINING DOT ABOVE, and dotless `ı` only
+/// to itself.
+/// Scripts whose folding maps to uppercase (Cherokee) are not covered.
+///
+/// Only available with the `unicode_fold` feature, which adds this type
+/// rather than changing an existing one. For ASCII-only folding, use a
+/// `NormalizingHashSet` with `to_ascii_lowercase`.
+pub struct UnicodeFoldHashSet {
+    entries: HashSet<Entry>,
+}
+
+impl Default for UnicodeFoldHashSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UnicodeFoldHashSet {
+    pub fn new() -> Self {
+        Self {
+            entries: HashSet::new(),
+        }
+    }
+
+    /// The key `value` is hashed and compared by.
+    pub fn fold(value: &str) -> String {
+        fold(value)
+    }
+
+    pub fn insert(&mut self, value: &str) -> bool {
+        self.entries.insert(Entry {
+            folded: fold(value),
+            original: value.to_string(),
+        })
+    }
+
+    pub fn contains(&self, value: &str) -> bool {
+        self.get(value).is_some()
+    }
+
+    /// The stored spelling matching `value`.
+    pub fn get(&self, value: &str) -> Option<&str> {
+        let folded = fold(value);
+        let hash = self.entries.hash_of(&folded);
+
+        self.entries
+            .find_hashed(hash, |e| e.folded == folded)
+            .map(|e| e.original.as_str())
+    }
+
+    pub fn remove(&mut self, value: &str) -> bool {
+        let folded = fold(value);
+        let hash = self.entries.hash_of(&folded);
+
+        self.entries
+            .take_hashed(hash, |e| e.folded == folded)
+            .is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Yields the stored spellings.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|e| e.original.as_str())
+    }
+}
+
+fn fold(value: &str) -> String {
+    let mut folded = String::with_capacity(value.len());
+
+    for c in value.chars().flat_map(char::to_lowercase) {
+        match full_fold_exception(c) {
+            Some(replacement) => folded.push_str(replacement),
+            None => folded.push(c),
+        }
+    }
+
+    folded
+}
+
+// Applied after lowercasing, so only lowercase inputs need entries (`ẞ`
+// has already become `ß`). Every CaseFolding.txt mapping with status C or
+// F whose result differs from `to_lowercase`, minus Cherokee, whose folds
+// go to uppercase.
+fn full_fold_exception(c: char) -> Option<&'static str> {
+    let replacement = match c {
+        'µ' => "μ",
+        'ß' => "ss",
+        'ŉ' => "ʼn",
+        'ſ' => "s",
+        'ǰ' => "j\u{30c}",
+        '\u{345}' | '\u{1fbe}' => "ι",
+        'ΐ' | '\u{1fd3}' => "ι\u{308}\u{301}",
+        'ΰ' | '\u{1fe3}' => "υ\u{308}\u{301}",
+        'ς' => "σ",
+        'ϐ' => "β",
+        'ϑ' => "θ",
+        'ϕ' => "φ",
+        'ϖ' => "π",
+        'ϰ' => "κ",
+        'ϱ' => "ρ",
+        'ϵ' => "ε",
+        'և' => "եւ",
+        'ᲀ' => "в",
+        'ᲁ' => "д",
+        'ᲂ' => "о",
+        'ᲃ' => "с",
+        'ᲄ' | 'ᲅ' => "т",
+        'ᲆ' => "ъ",
+        'ᲇ' => "ѣ",
+        'ᲈ' => "ꙋ",
+        'ẖ' => "h\u{331}",
+        'ẗ' => "t\u{308}",
+        'ẘ' => "w\u{30a}",
+        'ẙ' => "y\u{30a}",
+        'ẚ' => "aʾ",
+        'ẛ' => "ṡ",
+        'ὐ' => "υ\u{313}",
+        'ὒ' => "υ\u{313}\u{300}",
+        'ὔ' => "υ\u{313}\u{301}",
+        'ὖ' => "υ\u{313}\u{342}",
+        'ᾀ' => "ἀι",
+        'ᾁ' => "ἁι",
+        'ᾂ' => "ἂι",
+        'ᾃ' => "ἃι",
+        'ᾄ' => "ἄι",
+        'ᾅ' => "ἅι",
+        'ᾆ' => "ἆι",
+        'ᾇ' => "ἇι",
+        'ᾐ' => "ἠι",
+        'ᾑ' => "ἡι",
+        'ᾒ' => "ἢι",
+        'ᾓ' => "ἣι",
+        'ᾔ' => "ἤι",
+        'ᾕ' => "ἥι",
+        'ᾖ' => "ἦι",
+        'ᾗ' => "ἧι",
+        'ᾠ' => "ὠι",
+        'ᾡ' => "ὡι",
+        'ᾢ' => "ὢι",
+        'ᾣ' => "ὣι",
+        'ᾤ' => "ὤι",
+        'ᾥ' => "ὥι",
+        'ᾦ' => "ὦι",
+        'ᾧ' => "ὧι",
+        'ᾲ' => "ὰι",
+        'ᾳ' => "αι",
+        'ᾴ' => "άι",
+        'ᾶ' => "α\u{342}",
+        'ᾷ' => "α\u{342}ι",
+        'ῂ' => "ὴι",
+        'ῃ' => "ηι",
+        'ῄ' => "ήι",
+        'ῆ' => "η\u{342}",
+        'ῇ' => "η\u{342}ι",
+        'ῒ' => "ι\u{308}\u{300}",
+        'ῖ' => "ι\u{342}",
+        'ῗ' => "ι\u{308}\u{342}",
+        'ῢ' => "υ\u{308}\u{300}",
+        'ῤ' => "ρ\u{313}",
+        'ῦ' => "υ\u{342}",
+        'ῧ' => "υ\u{308}\u{342}",
+        'ῲ' => "ὼι",
+        'ῳ' => "ωι",
+        'ῴ' => "ώι",
+        'ῶ' => "ω\u{342}",
+        'ῷ' => "ω\u{342}ι",
+        'ﬀ' => "ff",
+        'ﬁ' => "fi",
+        'ﬂ' => "fl",
+        'ﬃ' => "ffi",
+        'ﬄ' => "ffl",
+        'ﬅ' | 'ﬆ' => "st",
+        'ﬓ' => "մն",
+        'ﬔ' => "մե",
+        'ﬕ' => "մի",
+        'ﬖ' => "վն",
+        'ﬗ' => "մխ",
+        _ => return None,
+    };
+
+    Some(replacement)
+}
+
+#[test]
+fn test_fold_ascii() {
+    let mut set = UnicodeFoldHashSet::new();
+    assert!(set.insert("Hello"));
+    assert!(!set.insert("HELLO"));
+    assert!(!set.insert("hello"));
+    assert_eq!(set.len(), 1);
+    assert_eq!(set.get("hElLo"), Some("Hello")); // First spelling is kept
+
+    assert!(set.remove("HELLO"));
+    assert!(set.is_empty());
+}
+
+#[test]
+fn test_fold_german_sharp_s() {
+    let mut set = UnicodeFoldHashSet::new();
+    assert!(set.insert("STRASSE"));
+    assert!(!set.insert("strasse"));
+    assert!(!set.insert("straße"));
+    assert!(!set.insert("STRAẞE")); // Capital sharp s
+    assert_eq!(set.len(), 1);
+
+    assert_eq!(
+        UnicodeFoldHashSet::fold("ΣΊΣΥΦΟΣ"),
+        UnicodeFoldHashSet::fold("σίσυφος")
+    );
+    assert_eq!(UnicodeFoldHashSet::fold("ﬁle"), "file");
+}
+
+#[test]
+fn test_fold_full_folding_entries() {
+    // Precomposed ǰ has no uppercase form but folds to j + combining caron
+    assert_eq!(UnicodeFoldHashSet::fold("\u{1f0}"), "j\u{30c}");
+    assert_eq!(
+        UnicodeFoldHashSet::fold("ǰ"),
+        UnicodeFoldHashSet::fold("J\u{30c}")
+    );
+    assert_eq!(
+        UnicodeFoldHashSet::fold("ᾈ"),
+        UnicodeFoldHashSet::fold("ἀι")
+    ); // Iota subscript
+    assert_eq!(UnicodeFoldHashSet::fold("ﬓ"), "մն");
+    assert_eq!(UnicodeFoldHashSet::fold("µ"), UnicodeFoldHashSet::fold("Μ")); // Micro sign
+
+    let mut set = UnicodeFoldHashSet::new();
+    set.insert("ǰ");
+    assert!(set.contains("J\u{30c}"));
+}
+
+#[test]
+fn test_fold_turkish_i() {
+    // Default folding: I/i pair up; dotted İ and dotless ı stay distinct
+    assert_eq!(UnicodeFoldHashSet::fold("I"), "i");
+    assert_eq!(UnicodeFoldHashSet::fold("İ"), "i\u{307}");
+    assert_eq!(UnicodeFoldHashSet::fold("ı"), "ı");
+
+    let mut set = UnicodeFoldHashSet::new();
+    set.insert("istanbul");
+    assert!(set.contains("ISTANBUL"));
+    assert!(!set.contains("İSTANBUL"));
+    assert!(!set.contains("ıstanbul"));
+}
@@ -0,0 +1,77 @@
+use crate::{HashSet, Iter};
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+type Validator<T, E> = Box<dyn Fn(&T) -> Result<(), E>>;
+
+/// A set that runs every inserted value through a validator first, so
+/// invariants are enforced at the set boundary instead of at call sites.
+pub struct ValidatingHashSet<T, E> {
+    set: HashSet<T>,
+    validator: Validator<T, E>,
+}
+
+impl<T, E> ValidatingHashSet<T, E>
+where
+    T: Hash + Eq,
+{
+    pub fn new(validator: impl Fn(&T) -> Result<(), E> + 'static) -> Self {
+        Self {
+            set: HashSet::new(),
+            validator: Box::new(validator),
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> Result<bool, E> {
+        (self.validator)(&value)?;
+        Ok(self.set.insert(value))
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.contains(value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.remove(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.set.iter()
+    }
+
+    pub fn into_inner(self) -> HashSet<T> {
+        self.set
+    }
+}
+
+#[test]
+fn test_validating_rejects_invalid() {
+    let mut set = ValidatingHashSet::new(|v: &i32| {
+        if *v < 0 {
+            Err(format!("negative: {v}"))
+        } else {
+            Ok(())
+        }
+    });
+    assert_eq!(set.insert(1), Ok(true));
+    assert_eq!(set.insert(1), Ok(false)); // Duplicate
+    assert_eq!(set.insert(-1), Err("negative: -1".to_string()));
+    assert_eq!(set.len(), 1); // Unchanged by the rejected insert
+    assert!(!set.contains(&-1));
+}
@@ -0,0 +1,117 @@
+use crate::{HashSet, Iter};
+use std::borrow::Borrow;
+use std::hash::Hash;
+
+type EvictHook<T> = Box<dyn FnMut(T)>;
+
+/// A size-bounded set for dedup caches. When an insert takes `len()` above
+/// the high watermark, elements are evicted in arbitrary (bucket) order
+/// until it is back down to the low watermark, and each one is handed to
+/// `on_evict`, e.g. to flush it downstream.
+pub struct WatermarkHashSet<T> {
+    set: HashSet<T>,
+    high: usize,
+    low: usize,
+    on_evict: EvictHook<T>,
+}
+
+impl<T> WatermarkHashSet<T>
+where
+    T: Hash + Eq,
+{
+    /// Panics unless `low <= high`.
+    pub fn new(high: usize, low: usize, on_evict: impl FnMut(T) + 'static) -> Self {
+        assert!(low <= high, "low watermark above the high watermark");
+
+        Self {
+            // Sized lazily: `high` is only a bound, and can be as large as
+            // `usize::MAX` to effectively disable eviction
+            set: HashSet::new(),
+            high,
+            low,
+            on_evict: Box::new(on_evict),
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> bool {
+        let inserted = self.set.insert(value);
+
+        if self.set.len() > self.high {
+            self.evict();
+        }
+
+        inserted
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.contains(value)
+    }
+
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        Q: Hash + Eq + ?Sized,
+        T: Borrow<Q>,
+    {
+        self.set.remove(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.set.iter()
+    }
+
+    fn evict(&mut self) {
+        let mut excess = self.set.len() - self.low;
+        let (evicted, _) = self.set.drain_filter_reporting(|_| {
+            let evict = excess > 0;
+            excess = excess.saturating_sub(1);
+            evict
+        });
+
+        for value in evicted {
+            (self.on_evict)(value);
+        }
+    }
+}
+
+#[test]
+fn test_watermark_eviction() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let evicted: Rc<RefCell<Vec<u32>>> = Rc::default();
+    let sink = Rc::clone(&evicted);
+    let mut set = WatermarkHashSet::new(100, 80, move |v| sink.borrow_mut().push(v));
+
+    for i in 0..150 {
+        set.insert(i);
+        assert!(set.len() <= 100);
+    }
+
+    // Evictions after inserting 100 and 121 and 142, then 7 more inserts
+    assert_eq!(set.len(), 87);
+    let evicted = evicted.take();
+    assert_eq!(evicted.len(), 150 - set.len());
+    assert!(evicted.iter().all(|v| !set.contains(v)));
+    assert!((0..150).all(|v| set.contains(&v) || evicted.contains(&v)));
+}
+
+#[test]
+fn test_watermark_unbounded_high() {
+    let mut set = WatermarkHashSet::new(usize::MAX, 0, |_: u32| unreachable!());
+    for i in 0..1000 {
+        set.insert(i);
+    }
+    assert_eq!(set.len(), 1000);
+}
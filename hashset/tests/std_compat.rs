@@ -0,0 +1,75 @@
+use std::collections::HashSet as StdHashSet;
+
+use hashset::HashSet;
+
+fn sorted<'a, I: IntoIterator<Item = &'a u32>>(iter: I) -> Vec<u32> {
+    let mut values: Vec<u32> = iter.into_iter().copied().collect();
+    values.sort();
+    values
+}
+
+// The body of each block is written against std and must compile unchanged
+// for both set types.
+macro_rules! std_style {
+    ($set:ty) => {{
+        let mut a: $set = (0..60).collect();
+        let b: $set = (40..100).step_by(2).collect();
+
+        a.reserve(50);
+        a.extend(100..110);
+        a.extend(&[200, 201]);
+        a.retain(|v| v % 7 != 0);
+
+        let union = sorted(a.union(&b));
+        let intersection = sorted(a.intersection(&b));
+        let difference = sorted(a.difference(&b));
+        let symmetric = sorted(a.symmetric_difference(&b));
+
+        let taken = a.take(&1);
+        let replaced = a.replace(2);
+        let inserted = a.replace(3000);
+        let got = a.get(&3).copied();
+
+        let subset = b.is_subset(&a);
+        let superset = a.is_superset(&b);
+        let disjoint = a.is_disjoint(&b);
+        let disjoint_empty = a.is_disjoint(&(500..510).collect());
+
+        a.shrink_to_fit();
+        let owned = {
+            let mut values: Vec<u32> = a.clone().into_iter().collect();
+            values.sort();
+            values
+        };
+        let borrowed = sorted(&a);
+        let drained = {
+            let mut values: Vec<u32> = a.drain().collect();
+            values.sort();
+            values
+        };
+
+        (
+            union,
+            intersection,
+            difference,
+            symmetric,
+            (taken, replaced, inserted, got),
+            (subset, superset, disjoint, disjoint_empty),
+            (owned, borrowed, drained, a.is_empty()),
+        )
+    }};
+}
+
+#[test]
+fn test_matches_std() {
+    assert_eq!(std_style!(HashSet<u32>), std_style!(StdHashSet<u32>));
+}
+
+// Unstable on std (`hash_set_entry`), so only checked here
+#[test]
+fn test_get_or_insert_with() {
+    let mut set: HashSet<String> = HashSet::new();
+    assert_eq!(set.get_or_insert_with("a", str::to_string), "a");
+    assert_eq!(set.get_or_insert_with("a", |_| unreachable!()), "a");
+    assert_eq!(set.len(), 1);
+}